@@ -0,0 +1,146 @@
+use std::time::Instant;
+
+// ============= 前台应用规则 =============
+//
+// 按前台窗口的 WM_CLASS 或进程名调整任务，例如：
+// 视频会议软件在前台时不锁屏、只在 IDE 或浏览器在前台时计时护眼任务、
+// 游戏连续在前台 50 分钟后立即提醒休息。
+
+/// 当前的活动窗口
+#[derive(Clone, serde::Serialize, Debug, Default, PartialEq, Eq)]
+pub struct ActiveWindow {
+    pub instance: Option<String>,  // WM_CLASS 的实例名
+    pub class: Option<String>,  // WM_CLASS 的类名
+    pub process: Option<String>,  // _NET_WM_PID 对应的进程名
+    pub fullscreen: bool,
+}
+
+impl ActiveWindow {
+    /// 实例名、类名或进程名与列表中任一项相同（不区分大小写）时返回该项
+    pub fn matches<'a>(&self, apps: &'a [String]) -> Option<&'a str> {
+        let names = [&self.instance, &self.class, &self.process];
+        apps.iter()
+            .find(|app| names.iter().any(|name| name.as_ref().is_some_and(|n| n.eq_ignore_ascii_case(app))))
+            .map(String::as_str)
+    }
+
+    /// 同一个应用的窗口视为同一焦点，切换窗口不打断连续时长
+    fn same_app(&self, other: &ActiveWindow) -> bool {
+        self.class == other.class && self.process == other.process
+    }
+}
+
+/// 规则在匹配的应用位于前台时执行的动作
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AppRuleAction {
+    /// 到期的提醒推迟到应用离开前台
+    Defer,
+    /// 只发通知，不弹出锁屏
+    NotifyOnly,
+    /// 只在应用位于前台时计时，其余时间冻结；无法得知活动窗口时照常计时
+    OnlyWhile,
+    /// 应用连续在前台达到 after_seconds 时立即提醒
+    TriggerAfter { after_seconds: u64 },
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+pub struct AppRule {
+    pub apps: Vec<String>,  // WM_CLASS 实例名、类名或进程名
+    #[serde(default)]
+    pub task_ids: Vec<String>,  // 适用的任务，为空时适用于全部任务
+    pub action: AppRuleAction,
+}
+
+impl AppRule {
+    fn applies_to(&self, task_id: &str) -> bool {
+        self.task_ids.is_empty() || self.task_ids.iter().any(|id| id == task_id)
+    }
+}
+
+/// 规则集合及焦点历史
+#[derive(Default)]
+pub struct AppRules {
+    rules: Vec<AppRule>,
+    focused: Option<ActiveWindow>,
+    focused_since: Option<Instant>,  // 当前应用连续位于前台的起点
+}
+
+impl AppRules {
+    pub fn set_rules(&mut self, rules: Vec<AppRule>) {
+        self.rules = rules;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn focused(&self) -> Option<&ActiveWindow> {
+        self.focused.as_ref()
+    }
+
+    /// 记录本次 tick 的活动窗口，应用变化时重新计算连续时长
+    pub fn update_focus(&mut self, window: Option<ActiveWindow>, now: Instant) {
+        let changed = match (&self.focused, &window) {
+            (Some(old), Some(new)) => !old.same_app(new),
+            (None, None) => false,
+            _ => true,
+        };
+        if changed {
+            self.focused_since = window.as_ref().map(|_| now);
+        }
+        self.focused = window;
+    }
+
+    /// 匹配当前前台应用且适用于该任务的规则
+    fn matching<'a>(&'a self, task_id: &'a str) -> impl Iterator<Item = (&'a AppRule, &'a str)> + 'a {
+        self.rules.iter()
+            .filter(move |rule| rule.applies_to(task_id))
+            .filter_map(|rule| Some((rule, self.focused.as_ref()?.matches(&rule.apps)?)))
+    }
+
+    /// 任务有「只在前台时计时」规则且当前前台不是这些应用。
+    /// 无法得知活动窗口时（Wayland、没有窗口管理器）照常计时，避免任务一直冻结
+    pub fn holds_timer(&self, task_id: &str) -> bool {
+        let Some(focused) = &self.focused else {
+            return false;
+        };
+        let only_while: Vec<&AppRule> = self.rules.iter()
+            .filter(|rule| rule.action == AppRuleAction::OnlyWhile && rule.applies_to(task_id))
+            .collect();
+        !only_while.is_empty() && !only_while.iter().any(|rule| focused.matches(&rule.apps).is_some())
+    }
+
+    /// 要求推迟任一任务的前台应用
+    pub fn defer_for(&self, task_ids: &[String]) -> Option<String> {
+        task_ids.iter()
+            .flat_map(|id| self.matching(id))
+            .find(|(rule, _)| rule.action == AppRuleAction::Defer)
+            .map(|(_, app)| app.to_string())
+    }
+
+    /// 是否有规则要求任一任务只发通知
+    pub fn notify_only(&self, task_ids: &[String]) -> bool {
+        task_ids.iter()
+            .flat_map(|id| self.matching(id))
+            .any(|(rule, _)| rule.action == AppRuleAction::NotifyOnly)
+    }
+
+    /// 当前前台应用要求推迟或只通知该任务的提醒，升级动作也一并暂缓
+    pub fn holds_reminder(&self, task_id: &str) -> bool {
+        self.matching(task_id)
+            .any(|(rule, _)| matches!(rule.action, AppRuleAction::Defer | AppRuleAction::NotifyOnly))
+    }
+
+    /// 应用连续在前台的时长（从 counted_from 和进入前台中较晚的时间算起）达到规则要求时返回 true
+    pub fn trigger_due(&self, task_id: &str, counted_from: Instant, now: Instant) -> bool {
+        let Some(since) = self.focused_since else {
+            return false;
+        };
+        let streak = now.saturating_duration_since(since.max(counted_from)).as_secs();
+        self.matching(task_id).any(|(rule, _)| match rule.action {
+            AppRuleAction::TriggerAfter { after_seconds } => streak >= after_seconds,
+            _ => false,
+        })
+    }
+}
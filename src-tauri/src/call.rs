@@ -0,0 +1,125 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::scheduler::CallSource;
+
+// ============= 通话检测 =============
+//
+// 视频会议或语音通话期间弹出锁屏会打断通话，到期提醒推迟到通话结束。
+// Linux: 扫描 /proc/<pid>/fd，有进程打开摄像头（/dev/video*）或 ALSA 录音设备
+//        （/dev/snd/pcmC*D*c）时视为通话中。PipeWire/PulseAudio 只在有录音流时
+//        才打开录音设备，空闲后会关闭，因此同样适用于通过音频服务录音的程序
+//        这只是启发式判断：虚拟摄像头（v4l2loopback、OBS）或常驻录音的程序也会被当作通话，
+//        因此调度器对通话推迟同样设有最长推迟时间
+// 其他平台没有 /proc，视为不在通话中
+
+/// 通过 /proc 下的文件描述符检测摄像头和麦克风是否被占用
+pub struct ProcCallSource {
+    root: PathBuf,
+}
+
+impl ProcCallSource {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn system() -> Self {
+        Self::new("/proc")
+    }
+
+    /// 任一进程打开了采集设备
+    fn capture_device_open(&self) -> bool {
+        let Ok(entries) = fs::read_dir(&self.root) else {
+            return false;
+        };
+        entries.flatten()
+            .filter(|entry| entry.file_name().to_str().is_some_and(|name| name.bytes().all(|b| b.is_ascii_digit())))
+            .any(|entry| process_uses_capture(&entry.path()))
+    }
+}
+
+impl CallSource for ProcCallSource {
+    fn in_call(&mut self) -> bool {
+        self.capture_device_open()
+    }
+}
+
+/// 进程的文件描述符中是否有采集设备；无权读取其他用户的进程时跳过
+fn process_uses_capture(process: &Path) -> bool {
+    let Ok(fds) = fs::read_dir(process.join("fd")) else {
+        return false;
+    };
+    fds.flatten()
+        .filter_map(|fd| fs::read_link(fd.path()).ok())
+        .any(|target| is_capture_device(&target))
+}
+
+/// /dev/video* 为摄像头，/dev/snd/pcmC<卡>D<设备>c 为录音设备（p 结尾的是播放设备）
+fn is_capture_device(path: &Path) -> bool {
+    let Some(path) = path.to_str() else {
+        return false;
+    };
+    if path.starts_with("/dev/video") {
+        return true;
+    }
+    path.strip_prefix("/dev/snd/pcmC")
+        .is_some_and(|device| device.contains('D') && device.ends_with('c'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    /// 临时目录中的假 /proc，测试结束时删除
+    struct FakeProc(PathBuf);
+
+    impl FakeProc {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir().join(format!("call-test-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+            Self(root)
+        }
+
+        /// 在 <pid>/fd 下创建指向 target 的文件描述符
+        fn open(&self, pid: &str, fd: u32, target: &str) {
+            let dir = self.0.join(pid).join("fd");
+            fs::create_dir_all(&dir).unwrap();
+            symlink(target, dir.join(fd.to_string())).unwrap();
+        }
+    }
+
+    impl Drop for FakeProc {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn camera_counts_as_call() {
+        let proc = FakeProc::new("camera");
+        proc.open("100", 0, "/dev/null");
+        proc.open("200", 7, "/dev/video0");
+        assert!(ProcCallSource::new(&proc.0).in_call());
+    }
+
+    #[test]
+    fn only_capture_pcm_counts_as_call() {
+        let proc = FakeProc::new("pcm");
+        proc.open("100", 3, "/dev/snd/pcmC0D0p");
+        proc.open("100", 4, "/dev/snd/controlC0");
+        let mut source = ProcCallSource::new(&proc.0);
+        assert!(!source.in_call());
+        proc.open("300", 5, "/dev/snd/pcmC1D0c");
+        assert!(source.in_call());
+    }
+
+    #[test]
+    fn ignores_non_process_entries() {
+        let proc = FakeProc::new("self");
+        proc.open("self", 3, "/dev/video0");
+        assert!(!ProcCallSource::new(&proc.0).in_call());
+        assert!(!ProcCallSource::new(proc.0.join("missing")).in_call());
+    }
+}
@@ -0,0 +1,245 @@
+use sha2::{Digest, Sha256};
+
+// ============= 紧急解锁 =============
+//
+// 严格模式隐藏了解锁按钮，真正有急事时需要另一条出路。紧急解锁由后端的锁屏会话校验：
+// PIN 或口令以加盐哈希保存在配置目录中，前端只提交用户输入；也可以要求逐字输入
+// 一句随机生成的句子，增加解锁的成本。每次紧急解锁都带时间戳记录到日志中。
+// 配置由后端保存，锁屏期间拒绝修改，前端无法绕过校验。
+
+/// 哈希迭代次数，增加暴力猜测 PIN 的成本
+const HASH_ITERATIONS: u32 = 100_000;
+
+/// 紧急解锁的校验方式
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmergencyMethod {
+    /// 数字 PIN
+    Pin,
+    /// 任意口令
+    Passphrase,
+    /// 输入随机生成的句子，不需要预先设置
+    Challenge,
+}
+
+/// 保存在配置目录中的紧急解锁设置，method 为 None 时未启用
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug, Default)]
+pub struct EmergencyUnlockConfig {
+    pub method: Option<EmergencyMethod>,
+    #[serde(default)]
+    salt: String,
+    #[serde(default)]
+    hash: String,
+    #[serde(default)]
+    iterations: u32,
+}
+
+/// 设置紧急解锁时的校验错误
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmergencySetupError {
+    /// PIN 必须是 4 到 12 位数字
+    InvalidPin,
+    /// 口令至少 8 个字符
+    PassphraseTooShort,
+    /// 无法生成随机盐
+    NoRandomness,
+}
+
+impl std::fmt::Display for EmergencySetupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::InvalidPin => "the PIN must be 4 to 12 digits",
+            Self::PassphraseTooShort => "the passphrase must be at least 8 characters",
+            Self::NoRandomness => "failed to generate a random salt",
+        };
+        f.write_str(text)
+    }
+}
+
+impl EmergencyUnlockConfig {
+    /// 使用 PIN 或口令，secret 只以加盐哈希保存；随机句子不需要 secret
+    pub fn with_secret(method: EmergencyMethod, secret: &str) -> Result<Self, EmergencySetupError> {
+        match method {
+            EmergencyMethod::Pin => {
+                if !(4..=12).contains(&secret.len()) || !secret.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(EmergencySetupError::InvalidPin);
+                }
+            }
+            EmergencyMethod::Passphrase => {
+                if secret.chars().count() < 8 {
+                    return Err(EmergencySetupError::PassphraseTooShort);
+                }
+            }
+            EmergencyMethod::Challenge => return Ok(Self::challenge()),
+        }
+        let mut salt = [0u8; 16];
+        getrandom::getrandom(&mut salt).map_err(|_| EmergencySetupError::NoRandomness)?;
+        let salt = to_hex(&salt);
+        let hash = hash_secret(&salt, secret, HASH_ITERATIONS);
+        Ok(Self { method: Some(method), salt, hash, iterations: HASH_ITERATIONS })
+    }
+
+    /// 使用随机句子，不保存任何秘密
+    pub fn challenge() -> Self {
+        Self { method: Some(EmergencyMethod::Challenge), ..Self::default() }
+    }
+
+    /// 校验 PIN 或口令
+    pub fn verify_secret(&self, answer: &str) -> bool {
+        if !matches!(self.method, Some(EmergencyMethod::Pin | EmergencyMethod::Passphrase)) || self.hash.is_empty() {
+            return false;
+        }
+        let computed = hash_secret(&self.salt, answer, self.iterations);
+        constant_time_eq(computed.as_bytes(), self.hash.as_bytes())
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// SHA-256(盐 + 秘密)，再以盐和上一轮结果迭代
+fn hash_secret(salt: &str, secret: &str, iterations: u32) -> String {
+    let mut digest = Sha256::new()
+        .chain_update(salt.as_bytes())
+        .chain_update(secret.as_bytes())
+        .finalize();
+    for _ in 1..iterations {
+        digest = Sha256::new()
+            .chain_update(salt.as_bytes())
+            .chain_update(digest)
+            .finalize();
+    }
+    to_hex(&digest)
+}
+
+/// 比较耗时与内容无关，避免通过耗时猜测哈希
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// 随机句子使用的单词，只含小写字母，方便在任何输入法下输入；64 个单词使随机字节均匀对应
+const CHALLENGE_WORDS: [&str; 64] = [
+    "apple", "river", "window", "quiet", "garden", "silver", "morning", "paper",
+    "mountain", "coffee", "yellow", "bridge", "orange", "winter", "pencil", "forest",
+    "ocean", "candle", "purple", "market", "summer", "island", "rabbit", "button",
+    "cloud", "ladder", "butter", "planet", "violin", "meadow", "pocket", "thunder",
+    "lemon", "marble", "feather", "harbor", "tunnel", "velvet", "walnut", "compass",
+    "blanket", "lantern", "pebble", "saddle", "timber", "anchor", "basket", "cotton",
+    "copper", "dragon", "engine", "falcon", "glacier", "hammer", "jacket", "kettle",
+    "mirror", "needle", "oyster", "parrot", "rocket", "shadow", "tomato", "wagon",
+];
+
+/// 随机句子的单词数
+const CHALLENGE_LENGTH: usize = 8;
+
+/// 生成一句随机句子；无法获取随机数时返回 None
+pub fn challenge_sentence() -> Option<String> {
+    let mut bytes = [0u8; CHALLENGE_LENGTH];
+    getrandom::getrandom(&mut bytes).ok()?;
+    let words: Vec<&str> = bytes.iter()
+        .map(|b| CHALLENGE_WORDS[*b as usize % CHALLENGE_WORDS.len()])
+        .collect();
+    Some(words.join(" "))
+}
+
+/// 输入的句子是否一致，忽略多余的空白
+pub fn challenge_matches(expected: &str, answer: &str) -> bool {
+    expected.split_whitespace().eq(answer.split_whitespace())
+}
+
+/// 紧急解锁日志中的一条记录
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct EmergencyUnlockRecord {
+    pub time: String,  // 本地时间，RFC 3339
+    pub method: EmergencyMethod,
+    pub task_ids: Vec<String>,
+    pub remaining: u64,  // 解锁时剩余的休息秒数
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pin_must_be_4_to_12_digits() {
+        assert_eq!(EmergencyUnlockConfig::with_secret(EmergencyMethod::Pin, "123").unwrap_err(), EmergencySetupError::InvalidPin);
+        assert_eq!(EmergencyUnlockConfig::with_secret(EmergencyMethod::Pin, "1234567890123").unwrap_err(), EmergencySetupError::InvalidPin);
+        assert_eq!(EmergencyUnlockConfig::with_secret(EmergencyMethod::Pin, "12a4").unwrap_err(), EmergencySetupError::InvalidPin);
+        assert!(EmergencyUnlockConfig::with_secret(EmergencyMethod::Pin, "1234").is_ok());
+    }
+
+    #[test]
+    fn passphrase_must_have_8_characters() {
+        assert_eq!(EmergencyUnlockConfig::with_secret(EmergencyMethod::Passphrase, "short").unwrap_err(), EmergencySetupError::PassphraseTooShort);
+        // 按字符而不是字节计算长度
+        assert_eq!(EmergencyUnlockConfig::with_secret(EmergencyMethod::Passphrase, "紧急解锁口令").unwrap_err(), EmergencySetupError::PassphraseTooShort);
+        assert!(EmergencyUnlockConfig::with_secret(EmergencyMethod::Passphrase, "let me out").is_ok());
+    }
+
+    #[test]
+    fn secret_is_stored_only_as_salted_hash() {
+        let config = EmergencyUnlockConfig::with_secret(EmergencyMethod::Pin, "2468").unwrap();
+        assert!(!config.hash.contains("2468"));
+        assert_eq!(config.salt.len(), 32);
+        let other = EmergencyUnlockConfig::with_secret(EmergencyMethod::Pin, "2468").unwrap();
+        assert_ne!(config.hash, other.hash);
+    }
+
+    #[test]
+    fn verify_secret_checks_pin_and_passphrase() {
+        let pin = EmergencyUnlockConfig::with_secret(EmergencyMethod::Pin, "2468").unwrap();
+        assert!(pin.verify_secret("2468"));
+        assert!(!pin.verify_secret("2469"));
+        assert!(!pin.verify_secret(""));
+
+        let passphrase = EmergencyUnlockConfig::with_secret(EmergencyMethod::Passphrase, "let me out").unwrap();
+        assert!(passphrase.verify_secret("let me out"));
+        assert!(!passphrase.verify_secret("let me out "));
+        assert!(!passphrase.verify_secret("Let me out"));
+    }
+
+    #[test]
+    fn tampered_salt_or_hash_fails_verification() {
+        let config = EmergencyUnlockConfig::with_secret(EmergencyMethod::Pin, "2468").unwrap();
+        let mut salted = config.clone();
+        salted.salt = "0".repeat(32);
+        assert!(!salted.verify_secret("2468"));
+        let mut emptied = config;
+        emptied.hash.clear();
+        assert!(!emptied.verify_secret("2468"));
+    }
+
+    #[test]
+    fn challenge_and_disabled_configs_never_verify_a_secret() {
+        assert!(!EmergencyUnlockConfig::challenge().verify_secret(""));
+        assert!(!EmergencyUnlockConfig::default().verify_secret(""));
+        let config = EmergencyUnlockConfig::with_secret(EmergencyMethod::Challenge, "").unwrap();
+        assert_eq!(config.method, Some(EmergencyMethod::Challenge));
+        assert!(config.hash.is_empty());
+    }
+
+    #[test]
+    fn constant_time_eq_compares_content_and_length() {
+        assert!(constant_time_eq(b"abcd", b"abcd"));
+        assert!(!constant_time_eq(b"abcd", b"abce"));
+        assert!(!constant_time_eq(b"abcd", b"abc"));
+        assert!(constant_time_eq(b"", b""));
+    }
+
+    #[test]
+    fn challenge_matches_ignores_extra_whitespace_only() {
+        assert!(challenge_matches("apple river window", "  apple   river\twindow\n"));
+        assert!(!challenge_matches("apple river window", "apple river"));
+        assert!(!challenge_matches("apple river window", "Apple river window"));
+        assert!(!challenge_matches("apple river window", "appleriver window"));
+    }
+
+    #[test]
+    fn challenge_sentence_uses_known_words() {
+        let sentence = challenge_sentence().unwrap();
+        let words: Vec<&str> = sentence.split(' ').collect();
+        assert_eq!(words.len(), CHALLENGE_LENGTH);
+        assert!(words.iter().all(|w| CHALLENGE_WORDS.contains(w)));
+    }
+}
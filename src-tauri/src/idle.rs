@@ -0,0 +1,594 @@
+use crate::scheduler::{IdleSource, InhibitSource};
+
+// ============= 跨平台空闲检测 =============
+//
+// 每个平台提供若干空闲检测后端，按优先级组成回退链：
+// 当前后端不可用时自动尝试下一个，并记录正在使用的后端。
+// Windows: GetLastInputInfo
+// macOS: ioreg HIDIdleTime
+// Linux: X11 XScreenSaver、GNOME Mutter IdleMonitor、org.freedesktop.ScreenSaver、logind IdleHint
+
+/// 按优先级依次尝试的空闲检测后端
+pub struct FallbackIdleSource {
+    backends: Vec<Box<dyn IdleSource + Send>>,
+    active: Option<usize>,
+}
+
+impl FallbackIdleSource {
+    pub fn new(backends: Vec<Box<dyn IdleSource + Send>>) -> Self {
+        Self { backends, active: None }
+    }
+
+    /// 当前平台的默认回退链
+    pub fn system() -> Self {
+        Self::new(platform_backends())
+    }
+}
+
+impl IdleSource for FallbackIdleSource {
+    fn name(&self) -> &'static str {
+        self.active.map(|i| self.backends[i].name()).unwrap_or("none")
+    }
+
+    fn idle_seconds(&mut self) -> Option<u64> {
+        // 优先使用上次可用的后端
+        if let Some(i) = self.active {
+            if let Some(seconds) = self.backends[i].idle_seconds() {
+                return Some(seconds);
+            }
+        }
+
+        for (i, backend) in self.backends.iter_mut().enumerate() {
+            if Some(i) == self.active {
+                continue;
+            }
+            if let Some(seconds) = backend.idle_seconds() {
+                self.active = Some(i);
+                return Some(seconds);
+            }
+        }
+
+        self.active = None;
+        None
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn platform_backends() -> Vec<Box<dyn IdleSource + Send>> {
+    vec![Box::new(WindowsIdle)]
+}
+
+#[cfg(target_os = "macos")]
+fn platform_backends() -> Vec<Box<dyn IdleSource + Send>> {
+    vec![Box::new(MacIdle)]
+}
+
+#[cfg(target_os = "linux")]
+fn platform_backends() -> Vec<Box<dyn IdleSource + Send>> {
+    let x11: Box<dyn IdleSource + Send> = Box::new(linux::X11Idle::new());
+    let mut backends: Vec<Box<dyn IdleSource + Send>> = vec![
+        Box::new(linux::MutterIdle::new()),
+        Box::new(linux::ScreenSaverIdle::new()),
+        Box::new(linux::LogindIdle::new()),
+    ];
+
+    // Wayland 下 XWayland 只能看到 X 客户端的输入，X11 后端放到最后
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some()
+        || std::env::var("XDG_SESSION_TYPE").is_ok_and(|t| t == "wayland");
+    if wayland {
+        backends.push(x11);
+    } else {
+        backends.insert(0, x11);
+    }
+    backends
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn platform_backends() -> Vec<Box<dyn IdleSource + Send>> {
+    Vec::new() // 不支持的平台没有可用后端
+}
+
+// ============= 屏保抑制检测 =============
+//
+// 视频播放器、演示软件等会抑制屏保，此时没有输入并不代表用户离开。
+// Linux: logind idle 抑制锁、GNOME SessionManager、org.freedesktop.PowerManagement.Inhibit
+// macOS: pmset 的 PreventUserIdleDisplaySleep 断言
+// Windows: 普通权限无法枚举电源请求，视为未抑制
+
+/// 当前平台的屏保抑制检测
+pub struct SystemInhibitSource {
+    #[cfg(target_os = "linux")]
+    inner: linux::Inhibitors,
+}
+
+impl SystemInhibitSource {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(target_os = "linux")]
+            inner: linux::Inhibitors::new(),
+        }
+    }
+}
+
+impl InhibitSource for SystemInhibitSource {
+    #[cfg(target_os = "linux")]
+    fn is_inhibited(&mut self) -> bool {
+        self.inner.is_inhibited()
+    }
+
+    #[cfg(target_os = "macos")]
+    fn is_inhibited(&mut self) -> bool {
+        use std::process::Command;
+
+        let Ok(output) = Command::new("pmset").args(["-g", "assertions"]).output() else {
+            return false;
+        };
+        // 汇总行格式: "   PreventUserIdleDisplaySleep    1"
+        String::from_utf8_lossy(&output.stdout).lines().any(|line| {
+            let mut parts = line.split_whitespace();
+            parts.next() == Some("PreventUserIdleDisplaySleep") && parts.next().is_some_and(|n| n != "0")
+        })
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    fn is_inhibited(&mut self) -> bool {
+        false
+    }
+}
+
+#[cfg(target_os = "windows")]
+struct WindowsIdle;
+
+#[cfg(target_os = "windows")]
+impl IdleSource for WindowsIdle {
+    fn name(&self) -> &'static str {
+        "win32"
+    }
+
+    fn idle_seconds(&mut self) -> Option<u64> {
+        use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+        use windows::Win32::System::SystemInformation::GetTickCount;
+
+        unsafe {
+            let mut lii = LASTINPUTINFO {
+                cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+                dwTime: 0,
+            };
+
+            if GetLastInputInfo(&mut lii).as_bool() {
+                let current_tick = GetTickCount();
+                let idle_ms = current_tick.wrapping_sub(lii.dwTime);
+                Some((idle_ms / 1000) as u64)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+struct MacIdle;
+
+#[cfg(target_os = "macos")]
+impl IdleSource for MacIdle {
+    fn name(&self) -> &'static str {
+        "iokit"
+    }
+
+    fn idle_seconds(&mut self) -> Option<u64> {
+        use std::process::Command;
+
+        // 使用 ioreg 命令获取空闲时间（更可靠的方式）
+        let output = Command::new("ioreg")
+            .args(["-c", "IOHIDSystem"])
+            .output()
+            .ok()?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // 查找 HIDIdleTime 字段，格式: "HIDIdleTime" = 1234567890
+        stdout
+            .lines()
+            .filter(|line| line.contains("HIDIdleTime"))
+            .filter_map(|line| line.split('=').nth(1))
+            .find_map(|value| value.trim().parse::<u64>().ok())
+            .map(|ns| ns / 1_000_000_000) // 纳秒转秒
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::ptr;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use x11::xlib::{Display, XCloseDisplay, XDefaultRootWindow, XFree, XOpenDisplay};
+    use x11::xss::{XScreenSaverAllocInfo, XScreenSaverInfo, XScreenSaverQueryInfo};
+    use zbus::blocking::Connection;
+    use zbus::zvariant::{OwnedObjectPath, OwnedValue};
+
+    use crate::scheduler::IdleSource;
+    use crate::session_linux;
+
+    /// X11 XScreenSaver 扩展，复用同一个显示连接
+    pub struct X11Idle {
+        display: *mut Display,
+        info: *mut XScreenSaverInfo,
+    }
+
+    // 连接只在定时器线程中使用
+    unsafe impl Send for X11Idle {}
+
+    impl X11Idle {
+        pub fn new() -> Self {
+            Self { display: ptr::null_mut(), info: ptr::null_mut() }
+        }
+
+        fn connect(&mut self) -> bool {
+            unsafe {
+                self.display = XOpenDisplay(ptr::null());
+                if self.display.is_null() {
+                    return false;
+                }
+                self.info = XScreenSaverAllocInfo();
+                if self.info.is_null() {
+                    self.disconnect();
+                    return false;
+                }
+            }
+            true
+        }
+
+        fn disconnect(&mut self) {
+            unsafe {
+                if !self.info.is_null() {
+                    XFree(self.info as *mut _);
+                    self.info = ptr::null_mut();
+                }
+                if !self.display.is_null() {
+                    XCloseDisplay(self.display);
+                    self.display = ptr::null_mut();
+                }
+            }
+        }
+    }
+
+    impl Drop for X11Idle {
+        fn drop(&mut self) {
+            self.disconnect();
+        }
+    }
+
+    impl IdleSource for X11Idle {
+        fn name(&self) -> &'static str {
+            "x11"
+        }
+
+        fn idle_seconds(&mut self) -> Option<u64> {
+            if self.display.is_null() && !self.connect() {
+                return None;
+            }
+
+            unsafe {
+                let root = XDefaultRootWindow(self.display);
+                if XScreenSaverQueryInfo(self.display, root, self.info) == 0 {
+                    // 扩展不可用，下次重新连接
+                    self.disconnect();
+                    return None;
+                }
+                // idle 为 c_ulong，32 位平台上不是 u64
+                let idle_ms: u64 = (*self.info).idle as _;
+                Some(idle_ms / 1000)
+            }
+        }
+    }
+
+    /// 按需建立并缓存 D-Bus 连接，失败时下次重试
+    fn cached_bus(conn: &mut Option<Connection>, system: bool) -> Option<&Connection> {
+        if conn.is_none() {
+            *conn = if system { Connection::system() } else { Connection::session() }.ok();
+        }
+        conn.as_ref()
+    }
+
+    /// GNOME Mutter IdleMonitor，Wayland 下的 GNOME 会话可用
+    pub struct MutterIdle {
+        conn: Option<Connection>,
+    }
+
+    impl MutterIdle {
+        pub fn new() -> Self {
+            Self { conn: None }
+        }
+    }
+
+    impl IdleSource for MutterIdle {
+        fn name(&self) -> &'static str {
+            "mutter"
+        }
+
+        fn idle_seconds(&mut self) -> Option<u64> {
+            let conn = cached_bus(&mut self.conn, false)?;
+            let reply = conn
+                .call_method(
+                    Some("org.gnome.Mutter.IdleMonitor"),
+                    "/org/gnome/Mutter/IdleMonitor/Core",
+                    Some("org.gnome.Mutter.IdleMonitor"),
+                    "GetIdletime",
+                    &(),
+                )
+                .ok()?;
+            let idle_ms: u64 = reply.body().deserialize().ok()?;
+            Some(idle_ms / 1000)
+        }
+    }
+
+    /// org.freedesktop.ScreenSaver.GetSessionIdleTime，KDE 等桌面提供
+    pub struct ScreenSaverIdle {
+        conn: Option<Connection>,
+    }
+
+    impl ScreenSaverIdle {
+        pub fn new() -> Self {
+            Self { conn: None }
+        }
+    }
+
+    impl IdleSource for ScreenSaverIdle {
+        fn name(&self) -> &'static str {
+            "screensaver"
+        }
+
+        fn idle_seconds(&mut self) -> Option<u64> {
+            let conn = cached_bus(&mut self.conn, false)?;
+            let reply = conn
+                .call_method(
+                    Some("org.freedesktop.ScreenSaver"),
+                    "/org/freedesktop/ScreenSaver",
+                    Some("org.freedesktop.ScreenSaver"),
+                    "GetSessionIdleTime",
+                    &(),
+                )
+                .ok()?;
+            // KDE 的实现返回毫秒
+            let idle_ms: u32 = reply.body().deserialize().ok()?;
+            Some(idle_ms as u64 / 1000)
+        }
+    }
+
+    /// logind 会话的 IdleHint，精度取决于桌面环境上报的频率
+    pub struct LogindIdle {
+        conn: Option<Connection>,
+        session_path: Option<OwnedObjectPath>,
+    }
+
+    impl LogindIdle {
+        pub fn new() -> Self {
+            Self { conn: None, session_path: None }
+        }
+
+        fn property(&self, name: &str) -> Option<OwnedValue> {
+            let conn = self.conn.as_ref()?;
+            let path = self.session_path.as_ref()?;
+            let reply = conn
+                .call_method(
+                    Some("org.freedesktop.login1"),
+                    path.as_str(),
+                    Some("org.freedesktop.DBus.Properties"),
+                    "Get",
+                    &("org.freedesktop.login1.Session", name),
+                )
+                .ok()?;
+            reply.body().deserialize().ok()
+        }
+    }
+
+    impl IdleSource for LogindIdle {
+        fn name(&self) -> &'static str {
+            "logind"
+        }
+
+        fn idle_seconds(&mut self) -> Option<u64> {
+            let conn = cached_bus(&mut self.conn, true)?;
+            if self.session_path.is_none() {
+                self.session_path = session_linux::find_session_path(conn).ok();
+            }
+
+            let idle: bool = self.property("IdleHint")?.downcast_ref().ok()?;
+            let since_us: u64 = self.property("IdleSinceHint")?.downcast_ref().ok()?;
+            let now_us = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_micros() as u64;
+            idle_from_hint(idle, since_us, now_us)
+        }
+    }
+
+    /// 按 logind 的 IdleHint 计算空闲秒数。IdleSinceHint 为提示最近一次变化时的 CLOCK_REALTIME 微秒数，
+    /// 为 0 说明桌面环境从未上报过空闲，提示一直为 false 不代表有输入，视为不可用
+    pub fn idle_from_hint(idle: bool, since_us: u64, now_us: u64) -> Option<u64> {
+        if since_us == 0 {
+            return None;
+        }
+        if !idle {
+            return Some(0);
+        }
+        Some(now_us.saturating_sub(since_us) / 1_000_000)
+    }
+
+    /// GNOME SessionManager 的抑制标志：阻止会话进入空闲
+    const GSM_INHIBIT_IDLE: u32 = 8;
+
+    /// 汇总各桌面环境的屏保抑制状态，任一来源抑制即视为抑制
+    pub struct Inhibitors {
+        session: Option<Connection>,
+        system: Option<Connection>,
+    }
+
+    impl Inhibitors {
+        pub fn new() -> Self {
+            Self { session: None, system: None }
+        }
+
+        pub fn is_inhibited(&mut self) -> bool {
+            self.logind() || self.gnome() || self.power_management()
+        }
+
+        /// logind 中 what 包含 idle 的阻塞锁，例如 systemd-inhibit --what=idle
+        fn logind(&mut self) -> bool {
+            let Some(conn) = cached_bus(&mut self.system, true) else {
+                return false;
+            };
+            let Ok(reply) = conn.call_method(
+                Some("org.freedesktop.login1"),
+                "/org/freedesktop/login1",
+                Some("org.freedesktop.login1.Manager"),
+                "ListInhibitors",
+                &(),
+            ) else {
+                return false;
+            };
+            let Ok(inhibitors) = reply.body().deserialize::<Vec<(String, String, String, String, u32, u32)>>() else {
+                return false;
+            };
+            inhibitors
+                .iter()
+                .any(|(what, _who, _why, mode, _uid, _pid)| mode == "block" && what.split(':').any(|w| w == "idle"))
+        }
+
+        /// GNOME 下 org.freedesktop.ScreenSaver.Inhibit 由 gnome-session 记录为 idle 抑制
+        fn gnome(&mut self) -> bool {
+            let Some(conn) = cached_bus(&mut self.session, false) else {
+                return false;
+            };
+            conn.call_method(
+                Some("org.gnome.SessionManager"),
+                "/org/gnome/SessionManager",
+                Some("org.gnome.SessionManager"),
+                "IsInhibited",
+                &(GSM_INHIBIT_IDLE,),
+            )
+            .ok()
+            .and_then(|reply| reply.body().deserialize::<bool>().ok())
+            .unwrap_or(false)
+        }
+
+        /// KDE 等桌面把屏保抑制转发到 PowerManagement.Inhibit
+        fn power_management(&mut self) -> bool {
+            let Some(conn) = cached_bus(&mut self.session, false) else {
+                return false;
+            };
+            conn.call_method(
+                Some("org.freedesktop.PowerManagement"),
+                "/org/freedesktop/PowerManagement/Inhibit",
+                Some("org.freedesktop.PowerManagement.Inhibit"),
+                "HasInhibit",
+                &(),
+            )
+            .ok()
+            .and_then(|reply| reply.body().deserialize::<bool>().ok())
+            .unwrap_or(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// 空闲秒数由测试控制的后端，None 为不可用
+    #[derive(Clone)]
+    struct FakeBackend {
+        name: &'static str,
+        idle: Arc<Mutex<Option<u64>>>,
+    }
+
+    impl FakeBackend {
+        fn new(name: &'static str, idle: Option<u64>) -> Self {
+            Self { name, idle: Arc::new(Mutex::new(idle)) }
+        }
+
+        fn set(&self, idle: Option<u64>) {
+            *self.idle.lock().unwrap() = idle;
+        }
+    }
+
+    impl IdleSource for FakeBackend {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn idle_seconds(&mut self) -> Option<u64> {
+            *self.idle.lock().unwrap()
+        }
+    }
+
+    fn chain(backends: &[&FakeBackend]) -> FallbackIdleSource {
+        FallbackIdleSource::new(backends.iter().map(|b| Box::new((*b).clone()) as Box<dyn IdleSource + Send>).collect())
+    }
+
+    #[test]
+    fn uses_the_first_available_backend() {
+        let x11 = FakeBackend::new("x11", None);
+        let mutter = FakeBackend::new("mutter", Some(30));
+        let logind = FakeBackend::new("logind", Some(0));
+        let mut source = chain(&[&x11, &mutter, &logind]);
+
+        assert_eq!(source.name(), "none");
+        assert_eq!(source.idle_seconds(), Some(30));
+        assert_eq!(source.name(), "mutter");
+    }
+
+    #[test]
+    fn falls_back_when_the_active_backend_fails() {
+        let mutter = FakeBackend::new("mutter", Some(30));
+        let logind = FakeBackend::new("logind", Some(60));
+        let mut source = chain(&[&mutter, &logind]);
+        source.idle_seconds();
+
+        mutter.set(None);
+        assert_eq!(source.idle_seconds(), Some(60));
+        assert_eq!(source.name(), "logind");
+        // 正在使用的后端可用时不切换回优先级更高的后端
+        mutter.set(Some(5));
+        assert_eq!(source.idle_seconds(), Some(60));
+        assert_eq!(source.name(), "logind");
+    }
+
+    #[test]
+    fn recovers_after_all_backends_were_unavailable() {
+        let mutter = FakeBackend::new("mutter", None);
+        let logind = FakeBackend::new("logind", None);
+        let mut source = chain(&[&mutter, &logind]);
+
+        assert_eq!(source.idle_seconds(), None);
+        assert_eq!(source.name(), "none");
+        logind.set(Some(10));
+        assert_eq!(source.idle_seconds(), Some(10));
+        assert_eq!(source.name(), "logind");
+        logind.set(None);
+        mutter.set(Some(20));
+        assert_eq!(source.idle_seconds(), Some(20));
+        assert_eq!(source.name(), "mutter");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn logind_hint_never_reported_is_unavailable() {
+        use super::linux::idle_from_hint;
+
+        let now_us = 1_800_000_000_000_000;
+        assert_eq!(idle_from_hint(false, 0, now_us), None);
+        assert_eq!(idle_from_hint(false, now_us - 5_000_000, now_us), Some(0));
+        assert_eq!(idle_from_hint(true, now_us - 90_000_000, now_us), Some(90));
+        // 时钟回拨时不出现负数
+        assert_eq!(idle_from_hint(true, now_us + 1, now_us), Some(0));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn logind_without_idle_hint_falls_through_the_chain() {
+        let logind = FakeBackend::new("logind", super::linux::idle_from_hint(false, 0, 1));
+        let x11 = FakeBackend::new("x11", Some(400));
+        let mut source = chain(&[&logind, &x11]);
+        assert_eq!(source.idle_seconds(), Some(400));
+        assert_eq!(source.name(), "x11");
+    }
+}
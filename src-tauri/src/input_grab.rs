@@ -0,0 +1,267 @@
+// ============= 严格模式的输入抓取 =============
+//
+// 严格模式只能让锁屏窗口置顶并定时重新聚焦，Alt+Tab、切换工作区和全局快捷键仍然有效。
+// 休息倒计时期间抓取键盘和鼠标，输入只发送到本程序的窗口（包括其他屏幕上的从属窗口）。
+// 只在 Linux 上编译，使用 X11 XGrabKeyboard/XGrabPointer。抓取必须使用 GTK 自己的显示连接，
+// owner_events 为 True 时本程序窗口上的输入照常处理，使用单独的连接会让锁屏窗口也收不到输入。
+// 程序退出或崩溃时 X 服务器会自动释放抓取。主循环卡住时无法在 GTK 的连接上释放，
+// 最后手段是在单独的连接上断开本程序的 GTK 连接（kill_client），X 服务器随即释放抓取
+// Wayland 不允许客户端抓取全局输入，报告为抓取失败
+
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// 抓取失败的原因
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GrabError {
+    /// 当前平台或显示服务器不支持
+    Unsupported,
+    /// 键盘抓取失败，附带 X 返回的状态
+    Keyboard(i32),
+    /// 鼠标抓取失败，附带 X 返回的状态
+    Pointer(i32),
+}
+
+/// XGrabKeyboard/XGrabPointer 的返回值
+fn grab_status_text(status: i32) -> &'static str {
+    match status {
+        1 => "already grabbed by another client",
+        2 => "invalid time",
+        3 => "window not viewable",
+        4 => "frozen by another grab",
+        _ => "unknown error",
+    }
+}
+
+impl std::fmt::Display for GrabError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported => f.write_str("input grab is only supported on X11"),
+            Self::Keyboard(status) => write!(f, "keyboard grab failed: {}", grab_status_text(*status)),
+            Self::Pointer(status) => write!(f, "pointer grab failed: {}", grab_status_text(*status)),
+        }
+    }
+}
+
+/// 主循环卡住时需要强制释放的抓取窗口。每轮通过 heartbeat 向主循环发送一个心跳，
+/// 连续 max_missed 轮在 interval 内都没有响应、且抓取仍未释放（grabbed_window 不为 0）时返回窗口 ID；
+/// 主循环只是暂时变慢而响应了任一心跳，或期间释放了抓取，都返回 None
+pub fn stuck_grab(
+    heartbeat: impl Fn(mpsc::Sender<()>),
+    grabbed_window: impl Fn() -> u32,
+    interval: Duration,
+    max_missed: u32,
+) -> Option<u32> {
+    let (tx, rx) = mpsc::channel();
+    for _ in 0..max_missed {
+        if grabbed_window() == 0 {
+            return None;
+        }
+        heartbeat(tx.clone());
+        if rx.recv_timeout(interval).is_ok() {
+            return None;
+        }
+    }
+    Some(grabbed_window()).filter(|window| *window != 0)
+}
+
+/// 抓取窗口所在显示连接的键盘和鼠标，必须在主线程调用。
+/// 已经抓取时再次调用不会出错，可以定期调用以恢复被打断的抓取
+pub fn grab_window(window: &gtk::ApplicationWindow) -> Result<(), GrabError> {
+    let (display, xid) = x11_handles(window).ok_or(GrabError::Unsupported)?;
+    unsafe { linux::grab(display, xid) }
+}
+
+/// 抓取所用的 X 窗口 ID，必须在主线程调用；Wayland 下或窗口尚未实现时返回 None
+pub fn window_xid(window: &gtk::ApplicationWindow) -> Option<u32> {
+    x11_handles(window).map(|(_, xid)| xid as u32)
+}
+
+/// 释放键盘和鼠标，必须在主线程调用；没有抓取时不做任何事
+pub fn release_window(window: &gtk::ApplicationWindow) {
+    if let Some((display, _)) = x11_handles(window) {
+        unsafe { linux::release(display) }
+    }
+}
+
+/// GTK 窗口的 X 显示连接和窗口 ID；Wayland 下或窗口尚未实现时返回 None
+fn x11_handles(window: &gtk::ApplicationWindow) -> Option<(*mut x11::xlib::Display, x11::xlib::Window)> {
+    use gtk::glib::translate::ToGlibPtr;
+    use gtk::prelude::*;
+
+    let gdk_window = window.window()?;
+    let display = gdk_window.display().downcast::<gdkx11::X11Display>().ok()?;
+    let x11_window = gdk_window.downcast::<gdkx11::X11Window>().ok()?;
+    let xdisplay = unsafe { gdkx11::ffi::gdk_x11_display_get_xdisplay(display.to_glib_none().0) };
+    Some((xdisplay, x11_window.xid()))
+}
+
+/// 直接操作 Xlib 连接，可以在 Xvfb 中用任意连接和窗口验证抓取与释放
+pub mod linux {
+    use std::os::raw::c_uint;
+
+    use x11rb::protocol::xproto::ConnectionExt;
+    use x11rb::rust_connection::RustConnection;
+
+    use x11::xlib::{
+        ButtonPressMask, ButtonReleaseMask, CurrentTime, Display, GrabModeAsync, GrabSuccess, PointerMotionMask,
+        True, Window, XFlush, XGrabKeyboard, XGrabPointer, XUngrabKeyboard, XUngrabPointer,
+    };
+
+    /// 抓取键盘和鼠标。一项失败时保留另一项的抓取，键盘抓取已能拦截 Alt+Tab 等快捷键
+    ///
+    /// # Safety
+    /// display 必须是有效的连接，且不能同时在其他线程使用
+    pub unsafe fn grab(display: *mut Display, window: Window) -> Result<(), super::GrabError> {
+        let keyboard = XGrabKeyboard(display, window, True, GrabModeAsync, GrabModeAsync, CurrentTime);
+        let pointer_mask = (ButtonPressMask | ButtonReleaseMask | PointerMotionMask) as c_uint;
+        let pointer = XGrabPointer(display, window, True, pointer_mask, GrabModeAsync, GrabModeAsync, 0, 0, CurrentTime);
+        XFlush(display);
+        if keyboard != GrabSuccess {
+            return Err(super::GrabError::Keyboard(keyboard));
+        }
+        if pointer != GrabSuccess {
+            return Err(super::GrabError::Pointer(pointer));
+        }
+        Ok(())
+    }
+
+    /// 释放键盘和鼠标；释放请求不等待回复，需要立即发送
+    ///
+    /// # Safety
+    /// display 必须是有效的连接，且不能同时在其他线程使用
+    pub unsafe fn release(display: *mut Display) {
+        XUngrabKeyboard(display, CurrentTime);
+        XUngrabPointer(display, CurrentTime);
+        XFlush(display);
+    }
+
+    /// 在新的连接上断开创建了 window 的客户端，X 服务器随即释放它持有的全部抓取。
+    /// 不需要该客户端配合，用于主循环卡住时释放本程序的抓取；被断开的 GTK 程序会退出。
+    /// display 为 None 时使用 DISPLAY 环境变量
+    pub fn kill_client(display: Option<&str>, window: u32) -> Result<(), String> {
+        let (conn, _) = RustConnection::connect(display).map_err(|e| e.to_string())?;
+        let result = conn.kill_client(window).map_err(|e| e.to_string())?
+            .check().map_err(|e| e.to_string());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::ffi::CString;
+    use std::time::Duration;
+
+    use x11::xlib::{Display, False, Window, XCloseDisplay, XCreateSimpleWindow, XDefaultRootWindow, XMapWindow, XOpenDisplay, XSync};
+
+    use super::{linux, stuck_grab, GrabError};
+    use crate::window::tests::Xvfb;
+
+    /// 一个 Xlib 连接和它创建的已映射窗口
+    struct Client {
+        display: *mut Display,
+        window: Window,
+    }
+
+    impl Client {
+        fn open(name: &str) -> Self {
+            let name = CString::new(name).unwrap();
+            unsafe {
+                let display = XOpenDisplay(name.as_ptr());
+                assert!(!display.is_null());
+                let window = XCreateSimpleWindow(display, XDefaultRootWindow(display), 0, 0, 100, 100, 0, 0, 0);
+                XMapWindow(display, window);
+                XSync(display, False);
+                Self { display, window }
+            }
+        }
+
+        fn grab(&self) -> Result<(), GrabError> {
+            unsafe { linux::grab(self.display, self.window) }
+        }
+
+        fn release(&self) {
+            unsafe {
+                linux::release(self.display);
+                XSync(self.display, False);
+            }
+        }
+    }
+
+    impl Drop for Client {
+        fn drop(&mut self) {
+            unsafe {
+                XCloseDisplay(self.display);
+            }
+        }
+    }
+
+    const HEARTBEAT: Duration = Duration::from_millis(10);
+
+    #[test]
+    fn stuck_main_loop_is_detected_after_repeated_misses() {
+        let sent = Cell::new(0);
+        let window = stuck_grab(|_| sent.set(sent.get() + 1), || 42, HEARTBEAT, 3);
+        assert_eq!(window, Some(42));
+        assert_eq!(sent.get(), 3);
+    }
+
+    #[test]
+    fn slow_main_loop_is_not_killed() {
+        let sent = Cell::new(0);
+        let pending = std::cell::RefCell::new(Vec::new());
+        // 主循环在第三个心跳时才处理完之前的任务
+        let window = stuck_grab(|tx| {
+            sent.set(sent.get() + 1);
+            pending.borrow_mut().push(tx);
+            if sent.get() == 3 {
+                let _ = pending.borrow()[0].send(());
+            }
+        }, || 42, HEARTBEAT, 5);
+        assert_eq!(window, None);
+        assert_eq!(sent.get(), 3);
+    }
+
+    #[test]
+    fn released_grab_is_not_killed() {
+        let sent = Cell::new(0);
+        let window = stuck_grab(|_| sent.set(sent.get() + 1), || if sent.get() < 2 { 42 } else { 0 }, HEARTBEAT, 5);
+        assert_eq!(window, None);
+        assert_eq!(sent.get(), 2);
+        assert_eq!(stuck_grab(|_| panic!("no grab, no heartbeat"), || 0, HEARTBEAT, 5), None);
+    }
+
+    #[test]
+    #[ignore = "requires Xvfb"]
+    fn grab_blocks_other_clients_until_released() {
+        let xvfb = Xvfb::start();
+        let first = Client::open(&xvfb.display);
+        let second = Client::open(&xvfb.display);
+
+        assert_eq!(first.grab(), Ok(()));
+        // 已经抓取时再次抓取不会出错
+        assert_eq!(first.grab(), Ok(()));
+        assert_eq!(second.grab(), Err(GrabError::Keyboard(1)));
+
+        first.release();
+        assert_eq!(second.grab(), Ok(()));
+        second.release();
+    }
+
+    #[test]
+    #[ignore = "requires Xvfb"]
+    fn kill_client_releases_grab_without_the_owner() {
+        let xvfb = Xvfb::start();
+        let stuck = Client::open(&xvfb.display);
+        let other = Client::open(&xvfb.display);
+        assert_eq!(stuck.grab(), Ok(()));
+        assert_eq!(other.grab(), Err(GrabError::Keyboard(1)));
+
+        linux::kill_client(Some(&xvfb.display), stuck.window as u32).unwrap();
+        // 连接已被服务器断开，再调用 Xlib 会触发 IO 错误并退出进程
+        std::mem::forget(stuck);
+        assert_eq!(other.grab(), Ok(()));
+        other.release();
+    }
+}
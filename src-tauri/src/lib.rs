@@ -1,9 +1,8 @@
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::{Duration, Instant};
-use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
 use std::thread;
 use tauri::{
     menu::{Menu, MenuItem, Submenu},
@@ -11,122 +10,34 @@ use tauri::{
     Manager, WindowEvent, State, Emitter, WebviewWindowBuilder, WebviewUrl, AppHandle,
 };
 use tauri_plugin_notification::NotificationExt;
-use url::form_urlencoded;
-
-// ============= 跨平台空闲检测 =============
-
-/// 获取系统空闲时间（秒）
-/// Windows: 使用 GetLastInputInfo
-/// macOS: 使用 CGEventSourceSecondsSinceLastEventType
-/// Linux: 使用 X11 screensaver extension
-fn get_idle_seconds() -> u64 {
-    #[cfg(target_os = "windows")]
-    {
-        get_idle_seconds_windows()
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        get_idle_seconds_macos()
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        get_idle_seconds_linux()
-    }
-
-    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-    {
-        0 // 不支持的平台返回 0
-    }
-}
-
-#[cfg(target_os = "windows")]
-fn get_idle_seconds_windows() -> u64 {
-    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
-    use windows::Win32::System::SystemInformation::GetTickCount;
-
-    unsafe {
-        let mut lii = LASTINPUTINFO {
-            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
-            dwTime: 0,
-        };
-
-        if GetLastInputInfo(&mut lii).as_bool() {
-            let current_tick = GetTickCount();
-            let idle_ms = current_tick.wrapping_sub(lii.dwTime);
-            (idle_ms / 1000) as u64
-        } else {
-            0
-        }
-    }
-}
-
-#[cfg(target_os = "macos")]
-fn get_idle_seconds_macos() -> u64 {
-    use std::process::Command;
-
-    // 使用 ioreg 命令获取空闲时间（更可靠的方式）
-    let output = Command::new("ioreg")
-        .args(["-c", "IOHIDSystem"])
-        .output();
-
-    if let Ok(output) = output {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        // 查找 HIDIdleTime 字段
-        for line in stdout.lines() {
-            if line.contains("HIDIdleTime") {
-                // 格式: "HIDIdleTime" = 1234567890
-                if let Some(value) = line.split('=').nth(1) {
-                    if let Ok(ns) = value.trim().parse::<u64>() {
-                        return ns / 1_000_000_000; // 纳秒转秒
-                    }
-                }
-            }
-        }
-    }
-    0
-}
+use chrono::{DateTime, Local, NaiveTime, TimeZone, Utc};
 
+mod app_rules;
+mod call;
+mod emergency;
+mod idle;
 #[cfg(target_os = "linux")]
-fn get_idle_seconds_linux() -> u64 {
-    use x11::xlib::{XOpenDisplay, XCloseDisplay, XDefaultRootWindow};
-    use x11::xss::{XScreenSaverQueryInfo, XScreenSaverAllocInfo};
-    use std::ptr;
-
-    unsafe {
-        let display = XOpenDisplay(ptr::null());
-        if display.is_null() {
-            return 0;
-        }
-
-        let info = XScreenSaverAllocInfo();
-        if info.is_null() {
-            XCloseDisplay(display);
-            return 0;
-        }
-
-        let root = XDefaultRootWindow(display);
-        let result = XScreenSaverQueryInfo(display, root, info);
-
-        let idle_ms = if result != 0 {
-            (*info).idle
-        } else {
-            0
-        };
-
-        x11::xlib::XFree(info as *mut _);
-        XCloseDisplay(display);
+mod input_grab;
+mod lock_session;
+mod monitors;
+mod schedule;
+mod scheduler;
+#[cfg(target_os = "linux")]
+mod session_linux;
+mod window;
 
-        (idle_ms / 1000) as u64
-    }
-}
+use app_rules::{ActiveWindow, AppRule};
+use emergency::{EmergencyMethod, EmergencyUnlockConfig, EmergencyUnlockRecord};
+use lock_session::{LockEndReason, LockEndedPayload, LockRefusal, LockRequest, LockSession, LockStep, LockTick};
+use monitors::{MonitorLayout, MonitorTracker, SlaveChange};
+use schedule::QuietHours;
+use scheduler::{Clock, CountdownInfo, DeferReason, EscalationAction, FullscreenPolicy, IdleSource, QuietPolicy, Scheduler, SystemClock, TaskConfig, TimerSnapshot};
 
 struct TrayState(Mutex<Option<TrayIcon>>);
 
 struct LockStateInner {
-    windows: Vec<String>,
-    args: Option<LockTaskArgs>,
+    monitors: MonitorTracker,  // 其他显示器与从属锁屏窗口的对应关系
+    session: Option<LockSession>,  // 当前的锁屏会话，None 为未锁屏
 }
 struct LockState(Mutex<LockStateInner>);
 
@@ -148,6 +59,16 @@ fn get_tray_text(key: &str, lang: &str) -> &'static str {
         ("pause", _) => "暂停",
         ("resume", "en-US") => "Resume",
         ("resume", _) => "继续",
+        ("pause_for", "en-US") => "Pause for…",
+        ("pause_for", _) => "暂停一段时间",
+        ("pause_30m", "en-US") => "30 Minutes",
+        ("pause_30m", _) => "30 分钟",
+        ("pause_1h", "en-US") => "1 Hour",
+        ("pause_1h", _) => "1 小时",
+        ("pause_2h", "en-US") => "2 Hours",
+        ("pause_2h", _) => "2 小时",
+        ("pause_tomorrow", "en-US") => "Until Tomorrow",
+        ("pause_tomorrow", _) => "直到明天",
         ("tooltip", "en-US") => "Health Reminder",
         ("tooltip", _) => "健康提醒助手",
         ("reset_submenu", "en-US") => "Reset Single Task",
@@ -165,6 +86,21 @@ fn get_tray_text(key: &str, lang: &str) -> &'static str {
     }
 }
 
+// 提醒未处理时长的提示文本
+fn get_unanswered_text(minutes: u64, lang: &str) -> String {
+    match lang {
+        "en-US" => format!("This reminder has been waiting for {} min", minutes),
+        _ => format!("该提醒已等待 {} 分钟", minutes),
+    }
+}
+
+fn get_call_deferred_text(lang: &str) -> &'static str {
+    match lang {
+        "en-US" => "Break due after your call",
+        _ => "通话结束后再休息",
+    }
+}
+
 // 获取任务显示标题（默认任务使用翻译，自定义任务使用原标题）
 fn get_task_display_title<'a>(task_id: &str, original_title: &'a str, lang: &str) -> std::borrow::Cow<'a, str> {
     match task_id {
@@ -177,64 +113,18 @@ fn get_task_display_title<'a>(task_id: &str, original_title: &'a str, lang: &str
 
 // ============= 后端定时器系统 =============
 
-#[derive(Clone, serde::Serialize, serde::Deserialize, Debug)]
-pub struct TaskConfig {
-    pub id: String,
-    pub title: String,
-    pub desc: String,
-    pub interval: u64,  // 分钟
-    pub enabled: bool,
-    pub icon: String,
-    #[serde(default)]
-    pub auto_reset_on_idle: bool,  // 空闲时自动重置
-}
-
-#[derive(Clone, Debug)]
-struct TaskTimer {
-    config: TaskConfig,
-    reset_time: Instant,
-    triggered: bool,  // 本轮是否已触发
-    disabled_at: Option<Instant>,  // 禁用时的时间点，用于计算暂停时长
-    snoozed: bool, // 是否处于推迟状态
-    snooze_count: u32, // 当前已推迟次数
-}
-
-struct TimerState {
-    tasks: HashMap<String, TaskTimer>,
-    paused: bool,
-    pause_start: Option<Instant>,
-    system_locked: bool,
-    lock_screen_active: bool,
-    lock_screen_start: Option<Instant>,  // 锁屏开始时间，用于补偿
-    // 空闲检测相关
-    idle_threshold_seconds: u64,  // 空闲阈值（秒），默认 300 秒 = 5 分钟
-    is_idle: bool,  // 当前是否处于空闲状态
-    idle_start: Option<Instant>,  // 进入空闲状态的时间点
-}
-
-impl TimerState {
-    fn new() -> Self {
-        Self {
-            tasks: HashMap::new(),
-            paused: false,
-            pause_start: None,
-            system_locked: false,
-            lock_screen_active: false,
-            lock_screen_start: None,
-            idle_threshold_seconds: 300,  // 默认 5 分钟
-            is_idle: false,
-            idle_start: None,
-        }
-    }
+static TIMER_STATE: std::sync::OnceLock<Mutex<Scheduler>> = std::sync::OnceLock::new();
+
+fn get_timer_state() -> &'static Mutex<Scheduler> {
+    TIMER_STATE.get_or_init(|| Mutex::new(Scheduler::new()))
 }
 
-static TIMER_STATE: std::sync::OnceLock<Mutex<TimerState>> = std::sync::OnceLock::new();
+static CLOCK: SystemClock = SystemClock;
 
-fn get_timer_state() -> &'static Mutex<TimerState> {
-    TIMER_STATE.get_or_init(|| Mutex::new(TimerState::new()))
-}
+/// 当前使用的空闲检测后端，"none" 表示没有可用后端
+static IDLE_BACKEND: Mutex<&'static str> = Mutex::new("none");
 
-#[cfg(target_os = "windows")]
+#[cfg(any(target_os = "windows", target_os = "linux"))]
 static SYSTEM_LOCKED: AtomicBool = AtomicBool::new(false);
 
 #[cfg(target_os = "windows")]
@@ -313,46 +203,42 @@ unsafe extern "system" fn session_wnd_proc(
     DefWindowProcW(hwnd, msg, wparam, lparam)
 }
 
-#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
-struct LockTaskArgs {
-    title: String,
-    desc: String,
-    duration: i32,
-    icon: String,
-    // Slave context
-    strict_mode: bool,
-    allow_strict_snooze: bool,
-    max_snooze_count: u32,
-    snooze_minutes: u32,
-    current_snooze_count: u32,
-}
+#[cfg(target_os = "linux")]
+fn start_session_monitor(app_handle: tauri::AppHandle) {
+    use session_linux::{LockSource, LockTracker};
+    use std::sync::Arc;
+
+    let tracker = Mutex::new(LockTracker::default());
+    let report = Arc::new(move |source: LockSource, locked: bool| {
+        let changed = tracker.lock().unwrap().update(source, locked);
+        if let Some(locked) = changed {
+            SYSTEM_LOCKED.store(locked, Ordering::SeqCst);
+            let event = if locked { "system-locked" } else { "system-unlocked" };
+            let _ = app_handle.emit(event, ());
+        }
+    });
 
-// ============= 定时器命令 =============
+    // logind 位于系统总线
+    let report_logind = report.clone();
+    std::thread::spawn(move || {
+        let Ok(conn) = zbus::blocking::Connection::system() else { return };
+        let Ok(session_path) = session_linux::find_session_path(&conn) else { return };
+        let _ = session_linux::watch_logind(&conn, &session_path, |locked| report_logind(LockSource::Logind, locked));
+    });
 
-#[derive(Clone, serde::Serialize)]
-struct CountdownInfo {
-    id: String,
-    remaining: u64,  // 剩余秒数
-    total: u64,      // 总秒数
-    enabled: bool,
-    snoozed: bool,   // 是否推迟中
-    snooze_remaining: u64, // 推迟剩余时间
-    snooze_count: u32, // 当前已推迟次数
+    // 屏保服务位于会话总线
+    std::thread::spawn(move || {
+        let Ok(conn) = zbus::blocking::Connection::session() else { return };
+        let _ = session_linux::watch_screensaver(&conn, |active| report(LockSource::ScreenSaver, active));
+    });
 }
 
-#[derive(Clone, serde::Serialize)]
-struct TaskTriggeredPayload {
-    id: String,
-    title: String,
-    desc: String,
-    icon: String,
-}
+// ============= 定时器命令 =============
 
 fn rebuild_tray_menu(app: &AppHandle) {
     let state = get_timer_state().lock().unwrap();
-    let is_paused = state.paused;
-    let mut tasks: Vec<TaskConfig> = state.tasks.values().map(|t| t.config.clone()).collect();
-    tasks.sort_by(|a, b| a.id.cmp(&b.id));
+    let is_paused = state.is_paused();
+    let tasks = state.task_configs();
     drop(state);
 
     // 获取当前语言
@@ -364,6 +250,12 @@ fn rebuild_tray_menu(app: &AppHandle) {
     let pause_text = if is_paused { get_tray_text("resume", &lang) } else { get_tray_text("pause", &lang) };
     let pause = MenuItem::with_id(app, "pause", pause_text, true, None::<&str>).unwrap();
 
+    let pause_for_items: Vec<MenuItem<tauri::Wry>> = PAUSE_FOR_MENU.iter()
+        .map(|(id, key, _)| MenuItem::with_id(app, *id, get_tray_text(key, &lang), true, None::<&str>).unwrap())
+        .collect();
+    let pause_for_refs: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> = pause_for_items.iter().map(|i| i as &dyn tauri::menu::IsMenuItem<tauri::Wry>).collect();
+    let pause_for_submenu = Submenu::with_items(app, get_tray_text("pause_for", &lang), true, &pause_for_refs).unwrap();
+
     let reset_prefix = get_tray_text("reset_prefix", &lang);
     let mut reset_items = Vec::new();
     for task in tasks {
@@ -380,6 +272,7 @@ fn rebuild_tray_menu(app: &AppHandle) {
     let menu = Menu::with_items(app, &[
         &show, 
         &pause, 
+        &pause_for_submenu, 
         &reset_all, 
         &reset_submenu, 
         &quit
@@ -397,448 +290,279 @@ fn rebuild_tray_menu(app: &AppHandle) {
 
 #[tauri::command]
 fn sync_tasks(app: tauri::AppHandle, tasks: Vec<TaskConfig>) {
-    {
-        let mut state = get_timer_state().lock().unwrap();
-        let now = Instant::now();
-
-        // 保留现有任务的计时状态，只更新配置
-        let mut new_tasks: HashMap<String, TaskTimer> = HashMap::new();
-
-        for task in tasks {
-            if let Some(existing) = state.tasks.get(&task.id) {
-                // 任务已存在
-                let interval_changed = existing.config.interval != task.interval;
-                let was_disabled = !existing.config.enabled;
-                let is_now_enabled = task.enabled;
-                let was_enabled = existing.config.enabled;
-                let is_now_disabled = !task.enabled;
-
-                if interval_changed {
-                    // interval 变了，重置计时
-                    new_tasks.insert(task.id.clone(), TaskTimer {
-                        config: task,
-                        reset_time: now,
-                        triggered: false,
-                        disabled_at: None,
-                        snoozed: false,
-                        snooze_count: 0,
-                    });
-                } else if was_disabled && is_now_enabled {
-                    // 从禁用变为启用，补偿禁用期间的时间
-                    let mut new_reset_time = existing.reset_time;
-                    if let Some(disabled_at) = existing.disabled_at {
-                        let disabled_duration = now.duration_since(disabled_at);
-                        new_reset_time += disabled_duration;
-                    }
-                    new_tasks.insert(task.id.clone(), TaskTimer {
-                        config: task,
-                        reset_time: new_reset_time,
-                        triggered: existing.triggered,
-                        disabled_at: None,
-                        snoozed: existing.snoozed,
-                        snooze_count: existing.snooze_count,
-                    });
-                } else if was_enabled && is_now_disabled {
-                    // 从启用变为禁用，记录禁用时间点
-                    new_tasks.insert(task.id.clone(), TaskTimer {
-                        config: task,
-                        reset_time: existing.reset_time,
-                        triggered: existing.triggered,
-                        disabled_at: Some(now),
-                        snoozed: existing.snoozed,
-                        snooze_count: existing.snooze_count,
-                    });
-                } else {
-                    // 状态没变，保留
-                    new_tasks.insert(task.id.clone(), TaskTimer {
-                        config: task,
-                        reset_time: existing.reset_time,
-                        triggered: existing.triggered,
-                        disabled_at: existing.disabled_at,
-                        snoozed: existing.snoozed,
-                        snooze_count: existing.snooze_count,
-                    });
-                }
-            } else {
-                // 新任务
-                new_tasks.insert(task.id.clone(), TaskTimer {
-                    config: task.clone(),
-                    reset_time: now,
-                    triggered: false,
-                    disabled_at: if task.enabled { None } else { Some(now) },
-                    snoozed: false,
-                    snooze_count: 0,
-                });
-            }
-        }
-
-        state.tasks = new_tasks;
-    } // drop lock
-
+    get_timer_state().lock().unwrap().sync_tasks(tasks, CLOCK.now());
     rebuild_tray_menu(&app);
 }
 
 #[tauri::command]
 fn timer_pause() {
-    let mut state = get_timer_state().lock().unwrap();
-    if !state.paused {
-        state.paused = true;
-        state.pause_start = Some(Instant::now());
+    get_timer_state().lock().unwrap().pause(CLOCK.now());
+}
+
+/// 托盘「暂停一段时间」子菜单：菜单 id、文本 key、暂停分钟数（None 为到明天）
+const PAUSE_FOR_MENU: [(&str, &str, Option<i64>); 4] = [
+    ("pause_for_30", "pause_30m", Some(30)),
+    ("pause_for_60", "pause_1h", Some(60)),
+    ("pause_for_120", "pause_2h", Some(120)),
+    ("pause_until_tomorrow", "pause_tomorrow", None),
+];
+
+/// 明天本地时间零点
+fn tomorrow_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    let tomorrow = now.with_timezone(&Local).date_naive() + chrono::Days::new(1);
+    Local.from_local_datetime(&tomorrow.and_time(NaiveTime::MIN))
+        .earliest()
+        .map_or(now + chrono::Duration::days(1), |at| at.with_timezone(&Utc))
+}
+
+/// 暂停到指定时间并通知前端，返回恢复时间（Unix 毫秒）
+fn pause_timer_until(app: &AppHandle, until: DateTime<Utc>) -> i64 {
+    get_timer_state().lock().unwrap().pause_until(until, CLOCK.now());
+    let until = until.timestamp_millis();
+    let _ = app.emit("timer-paused", until);
+    until
+}
+
+/// 暂停指定分钟数，或暂停到指定时间（Unix 毫秒），到时自动恢复
+#[tauri::command]
+fn timer_pause_for(app: AppHandle, minutes: Option<u64>, until: Option<i64>) -> Result<i64, String> {
+    let now = CLOCK.now();
+    let until = match (minutes, until) {
+        (Some(0), None) => return Err("pause duration must be at least one minute".into()),
+        (Some(minutes), None) => i64::try_from(minutes).ok()
+            .and_then(chrono::Duration::try_minutes)
+            .and_then(|duration| now.wall.checked_add_signed(duration))
+            .ok_or("pause duration is too long")?,
+        (None, Some(until)) => DateTime::from_timestamp_millis(until).ok_or("invalid pause end time")?,
+        _ => return Err("either minutes or until is required".into()),
+    };
+    if until <= now.wall {
+        return Err("pause end time is in the past".into());
     }
+    Ok(pause_timer_until(&app, until))
 }
 
 #[tauri::command]
 fn timer_resume() {
-    let mut state = get_timer_state().lock().unwrap();
-    if state.paused {
-        if let Some(pause_start) = state.pause_start {
-            let pause_duration = pause_start.elapsed();
-            // 补偿暂停时间
-            for timer in state.tasks.values_mut() {
-                timer.reset_time += pause_duration;
-            }
-        }
-        state.paused = false;
-        state.pause_start = None;
-    }
+    get_timer_state().lock().unwrap().resume(CLOCK.now());
 }
 
 #[tauri::command]
 fn timer_reset_task(task_id: String) {
-    let mut state = get_timer_state().lock().unwrap();
-    let now = Instant::now();
-    if let Some(timer) = state.tasks.get_mut(&task_id) {
-        timer.reset_time = now;
-        timer.triggered = false;
-        timer.snoozed = false;
-        timer.snooze_count = 0;
-        // 如果任务禁用，也更新 disabled_at
-        if timer.disabled_at.is_some() {
-            timer.disabled_at = Some(now);
-        }
-    }
+    get_timer_state().lock().unwrap().reset_task(&task_id, CLOCK.now());
 }
 
+/// 完成一次休息，同时重置这些任务抵扣的任务，返回重置的全部任务 id
 #[tauri::command]
-fn timer_reset_all() {
-    let mut state = get_timer_state().lock().unwrap();
-    let now = Instant::now();
-    for timer in state.tasks.values_mut() {
-        timer.reset_time = now;
-        timer.triggered = false;
-        timer.snoozed = false;
-        timer.snooze_count = 0;
-        // 如果任务禁用，也更新 disabled_at
-        if timer.disabled_at.is_some() {
-            timer.disabled_at = Some(now);
-        }
-    }
+fn timer_complete_break(task_ids: Vec<String>) -> Vec<String> {
+    get_timer_state().lock().unwrap().complete_break(&task_ids, CLOCK.now())
 }
 
 #[tauri::command]
-fn timer_snooze_task(task_id: String, minutes: u64) {
-    let mut state = get_timer_state().lock().unwrap();
-    let now = Instant::now();
-    if let Some(timer) = state.tasks.get_mut(&task_id) {
-        let snooze_duration = Duration::from_secs(minutes * 60);
-        let total_duration = Duration::from_secs(timer.config.interval * 60);
-
-        // reset_time = now + snooze - total
-        if snooze_duration >= total_duration {
-            timer.reset_time = now + (snooze_duration - total_duration);
-        } else {
-            timer.reset_time = now - (total_duration - snooze_duration);
-        }
+fn timer_reset_all() {
+    get_timer_state().lock().unwrap().reset_all(CLOCK.now());
+}
 
-        timer.triggered = false;
-        timer.snoozed = true;
-        timer.snooze_count += 1;
+/// 严格模式的锁屏会话期间不能关闭严格模式
+#[tauri::command]
+fn timer_set_strict_mode(lock_state: State<LockState>, enabled: bool) -> Result<(), String> {
+    let guard = lock_state.0.lock().unwrap();
+    if !enabled && guard.session.as_ref().is_some_and(|session| session.is_strict()) {
+        return Err("strict mode cannot be disabled during a strict lock".to_string());
     }
+    get_timer_state().lock().unwrap().set_strict_mode(enabled);
+    Ok(())
+}
+
+#[tauri::command]
+fn timer_snooze_task(task_id: String, minutes: u64) -> Result<Vec<String>, String> {
+    get_timer_state().lock().unwrap()
+        .snooze_task(&task_id, minutes, CLOCK.now())
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 fn get_countdowns() -> Vec<CountdownInfo> {
-    let state = get_timer_state().lock().unwrap();
-    let now = Instant::now();
+    get_timer_state().lock().unwrap().countdowns(CLOCK.now())
+}
 
-    state.tasks.values().map(|timer| {
-        let total_secs = timer.config.interval * 60;
+#[tauri::command]
+fn timer_set_system_locked(locked: bool) {
+    get_timer_state().lock().unwrap().set_system_locked(locked, CLOCK.now());
+}
 
-        // 如果任务被禁用，使用禁用时间点计算 elapsed，这样时间就"冻结"了
-        let effective_now = if let Some(disabled_at) = timer.disabled_at {
-            disabled_at
-        } else {
-            now
-        };
+#[tauri::command]
+fn set_idle_threshold(seconds: u64) {
+    get_timer_state().lock().unwrap().set_idle_threshold(seconds);
+}
 
-        let remaining = if timer.reset_time > effective_now {
-            let wait_time = timer.reset_time.duration_since(effective_now).as_secs();
-            total_secs + wait_time
-        } else {
-            let elapsed = effective_now.saturating_duration_since(timer.reset_time).as_secs();
-            if elapsed >= total_secs { 0 } else { total_secs - elapsed }
-        };
-        
-        let snooze_remaining = if timer.reset_time > now {
-            timer.reset_time.duration_since(now).as_secs()
-        } else {
-            0
-        };
+#[tauri::command]
+fn get_idle_threshold() -> u64 {
+    get_timer_state().lock().unwrap().idle_threshold()
+}
 
-        CountdownInfo {
-            id: timer.config.id.clone(),
-            remaining,
-            total: total_secs,
-            enabled: timer.config.enabled,
-            snoozed: timer.snoozed,
-            snooze_remaining,
-            snooze_count: timer.snooze_count,
-        }
-    }).collect()
+/// 设置提醒合并窗口，0 表示不合并
+#[tauri::command]
+fn set_merge_window(seconds: u64) {
+    get_timer_state().lock().unwrap().set_merge_window((seconds > 0).then_some(seconds));
 }
 
+/// 设置前台全屏时到期提醒的处理方式，max_defer_seconds 为 0 表示不限推迟时长
 #[tauri::command]
-fn timer_set_system_locked(locked: bool) {
-    let mut state = get_timer_state().lock().unwrap();
-    let now = Instant::now();
-
-    if locked && !state.system_locked {
-        // 刚锁屏，记录暂停时间
-        state.system_locked = true;
-        if state.pause_start.is_none() {
-            state.pause_start = Some(now);
-        }
-    } else if !locked && state.system_locked {
-        // 解锁
-        let pause_duration = state.pause_start.map(|s| s.elapsed());
-
-        for timer in state.tasks.values_mut() {
-            if timer.config.auto_reset_on_idle {
-                // 勾选了"空闲重置"，直接重置为初始值
-                timer.reset_time = now;
-                timer.triggered = false;
-            } else if let Some(duration) = pause_duration {
-                // 没有勾选，补偿暂停时间
-                timer.reset_time += duration;
-            }
-        }
+fn set_fullscreen_policy(policy: FullscreenPolicy, max_defer_seconds: u64) {
+    get_timer_state().lock().unwrap().set_fullscreen_policy(policy, (max_defer_seconds > 0).then_some(max_defer_seconds));
+}
 
-        state.system_locked = false;
-        if !state.paused {
-            state.pause_start = None;
-        }
-    }
+/// 设置是否在通话（摄像头或麦克风被占用）时推迟提醒，max_defer_seconds 为 0 表示推迟到通话结束
+#[tauri::command]
+fn set_call_detection(enabled: bool, max_defer_seconds: u64) {
+    get_timer_state().lock().unwrap().set_call_detection(enabled, (max_defer_seconds > 0).then_some(max_defer_seconds));
 }
 
+/// 设置前台应用规则
 #[tauri::command]
-fn timer_set_lock_screen_active(active: bool) {
-    let mut state = get_timer_state().lock().unwrap();
-    if active && !state.lock_screen_active {
-        // 刚进入锁屏模式，记录开始时间
-        state.lock_screen_active = true;
-        state.lock_screen_start = Some(Instant::now());
-    } else if !active && state.lock_screen_active {
-        // 退出锁屏模式，补偿锁屏期间的时间
-        if let Some(lock_start) = state.lock_screen_start {
-            let lock_duration = lock_start.elapsed();
-            for timer in state.tasks.values_mut() {
-                timer.reset_time += lock_duration;
-            }
-        }
-        state.lock_screen_active = false;
-        state.lock_screen_start = None;
-    }
+fn set_app_rules(rules: Vec<AppRule>) {
+    get_timer_state().lock().unwrap().set_app_rules(rules);
 }
 
+/// 当前的前台应用，供设置界面填写规则时参考
 #[tauri::command]
-fn set_idle_threshold(seconds: u64) {
-    let mut state = get_timer_state().lock().unwrap();
-    state.idle_threshold_seconds = seconds;
+fn get_focused_app() -> Option<ActiveWindow> {
+    get_timer_state().lock().unwrap().focused_window().cloned()
 }
 
+/// 设置全局免打扰时段及期间到期提醒的处理方式，quiet_hours 为 null 时关闭
 #[tauri::command]
-fn get_idle_threshold() -> u64 {
-    let state = get_timer_state().lock().unwrap();
-    state.idle_threshold_seconds
+fn set_quiet_hours(quiet_hours: Option<QuietHours>, policy: QuietPolicy) {
+    get_timer_state().lock().unwrap().set_quiet_hours(quiet_hours, policy);
 }
 
-#[derive(Clone, serde::Serialize)]
-struct IdleStatus {
-    is_idle: bool,
-    idle_seconds: u64,
-    threshold: u64,
+#[tauri::command]
+fn get_idle_backend() -> &'static str {
+    *IDLE_BACKEND.lock().unwrap()
+}
+
+#[tauri::command]
+fn set_offline_break_threshold(seconds: u64) {
+    get_timer_state().lock().unwrap().set_offline_break_threshold(seconds);
 }
 
 fn start_timer_thread(app_handle: AppHandle) {
     thread::spawn(move || {
+        let mut idle_source = idle::FallbackIdleSource::system();
+        let mut inhibit_source = idle::SystemInhibitSource::new();
+        let mut window_source = window::SystemWindowSource::new();
+        let mut call_source = call::ProcCallSource::system();
+        let mut ticks: u64 = 0;
         loop {
             thread::sleep(Duration::from_secs(1));
 
-            let mut tasks_to_trigger: Vec<TaskTriggeredPayload> = Vec::new();
-            let mut idle_status_changed = false;
-            let current_idle_status;
+            // 定期保存计时进度，防止崩溃或重启后从头计时
+            ticks += 1;
+            if ticks.is_multiple_of(TIMER_PROGRESS_SAVE_INTERVAL) {
+                save_timer_progress();
+            }
 
-            {
+            let now = CLOCK.now();
+            let (slept, resumed, output, paused_countdowns) = {
                 let mut state = get_timer_state().lock().unwrap();
+                let slept = state.detect_sleep(now);
+                // 定时暂停到期，自动恢复计时
+                let resumed = state.resume_if_due(now);
+                // 如果暂停、系统锁屏或锁屏模式激活，调度器会跳过本次检查
+                let output = state.tick(now, &mut idle_source, &mut inhibit_source, &mut window_source, &mut call_source);
+                // 定时暂停和免打扰期间仍发送倒计时，供前端显示剩余时间
+                let paused_countdowns = (state.pause_remaining(now).is_some() || state.is_quiet())
+                    .then(|| state.countdowns(now));
+                (slept, resumed, output, paused_countdowns)
+            };
 
-                // 如果暂停、系统锁屏或锁屏模式激活，跳过检查
-                if state.paused || state.system_locked || state.lock_screen_active {
-                    continue;
-                }
-
-                let now = Instant::now();
-                let idle_seconds = get_idle_seconds();
-                let threshold = state.idle_threshold_seconds;
-                let was_idle = state.is_idle;
-                let is_now_idle = idle_seconds >= threshold;
-
-                // 检测空闲状态变化
-                if is_now_idle && !was_idle {
-                    // 刚进入空闲状态
-                    state.is_idle = true;
-                    state.idle_start = Some(now);
-                    idle_status_changed = true;
-
-                    // 重置所有勾选了「空闲重置」的任务
-                    for timer in state.tasks.values_mut() {
-                        if timer.config.auto_reset_on_idle && timer.config.enabled {
-                            timer.reset_time = now;
-                            timer.triggered = false;
-                        }
-                    }
-                } else if !is_now_idle && was_idle {
-                    // 刚从空闲状态恢复
-                    state.is_idle = false;
-
-                    // 重新开始倒计时（从头开始）
-                    for timer in state.tasks.values_mut() {
-                        if timer.config.auto_reset_on_idle && timer.config.enabled {
-                            timer.reset_time = now;
-                            timer.triggered = false;
-                        }
-                    }
-
-                    state.idle_start = None;
-                    idle_status_changed = true;
-                }
-
-                current_idle_status = IdleStatus {
-                    is_idle: state.is_idle,
-                    idle_seconds,
-                    threshold,
-                };
-
-                // 如果处于空闲状态，不检查任务触发（计时暂停）
-                if state.is_idle {
-                    // 空闲时不触发任何任务，但仍然发送倒计时更新
-                } else {
-                    // 正常检查任务触发
-                    for timer in state.tasks.values_mut() {
-                        if !timer.config.enabled || timer.triggered {
-                            continue;
-                        }
+            if resumed {
+                let _ = app_handle.emit("timer-auto-resumed", ());
+            }
+            if let Some(countdowns) = paused_countdowns {
+                let _ = app_handle.emit("countdown-update", countdowns);
+            }
 
-                        let elapsed = now.saturating_duration_since(timer.reset_time).as_secs();
-                        let total_secs = timer.config.interval * 60;
+            // 空闲检测后端切换时通知前端
+            let backend = idle_source.name();
+            if std::mem::replace(&mut *IDLE_BACKEND.lock().unwrap(), backend) != backend {
+                let _ = app_handle.emit("idle-backend-changed", backend);
+            }
 
-                        if elapsed >= total_secs {
-                            // 触发提醒
-                            tasks_to_trigger.push(TaskTriggeredPayload {
-                                id: timer.config.id.clone(),
-                                title: timer.config.title.clone(),
-                                desc: timer.config.desc.clone(),
-                                icon: timer.config.icon.clone(),
-                            });
+            // 系统从休眠中恢复
+            if let Some(seconds) = slept {
+                let _ = app_handle.emit("system-resumed", seconds);
+            }
 
-                            // 标记为已触发，等待用户操作（重置或推迟）
-                            timer.triggered = true;
-                        }
+            // 推进锁屏会话并广播状态，倒计时结束且设置了自动解锁时结束锁屏
+            let lock_step = app_handle.state::<LockState>().0.lock().unwrap()
+                .session.as_mut()
+                .map(|session| session.tick(now.instant));
+            match lock_step {
+                Some(LockStep::Completed) => finish_lock_session(&app_handle, LockEndReason::Completed),
+                Some(LockStep::Running | LockStep::WaitingConfirm) => {
+                    if let Some(tick) = lock_tick(&app_handle) {
+                        let _ = app_handle.emit("lock-tick", tick);
                     }
+                    lock_watchdog(&app_handle);
                 }
+                None => {}
             }
 
+            let Some(output) = output else {
+                continue;
+            };
+
             // 发送触发事件到前端
-            for task in tasks_to_trigger {
+            for task in output.triggered {
                 let _ = app_handle.emit("task-triggered", task);
             }
 
-            // 发送空闲状态更新（只在状态变化时发送，或每 5 秒发送一次状态）
-            if idle_status_changed {
-                let _ = app_handle.emit("idle-status-changed", current_idle_status.clone());
-            }
-
-            // 发送倒计时更新
-            let countdowns = get_countdowns();
-            let _ = app_handle.emit("countdown-update", countdowns);
-
-            // ============= 锁屏看门狗 (Watchdog) =============
-            // 确保锁屏窗口始终置顶且聚焦，防止被最小化
-            let is_locked = get_timer_state().lock().unwrap().lock_screen_active;
-            if is_locked {
-                // 主窗口
-                if let Some(window) = app_handle.get_webview_window("main") {
-                    if !window.is_visible().unwrap_or(false) { let _ = window.show(); }
-                    let _ = window.unminimize();
-                    if !window.is_focused().unwrap_or(false) { let _ = window.set_focus(); }
-                    let _ = window.set_always_on_top(true);
+            // 到期提醒因通话、前台全屏等原因被推迟
+            if let Some(deferred) = output.deferred {
+                // 通话期间前端可能被会议窗口遮挡，额外发送系统通知
+                if deferred.reason == DeferReason::Call {
+                    let lang = app_handle.state::<LanguageState>().0.lock().unwrap().clone();
+                    let _ = app_handle.notification()
+                        .builder()
+                        .title(get_call_deferred_text(&lang))
+                        .show();
                 }
+                let _ = app_handle.emit("reminder-deferred", deferred);
+            }
 
-                let lock_state = app_handle.state::<LockState>();
-                let mut guard = lock_state.0.lock().unwrap();
-                let windows = guard.windows.clone();
-                let args = guard.args.clone();
-
-                for label in &windows {
-                    if let Some(window) = app_handle.get_webview_window(label) {
-                        if !window.is_visible().unwrap_or(false) { let _ = window.show(); }
-                        if !window.is_focused().unwrap_or(false) { let _ = window.set_focus(); }
-                        let _ = window.set_always_on_top(true);
+            // 提醒长时间未处理，按任务的升级策略再次提醒
+            for escalation in output.escalations {
+                match escalation.action {
+                    EscalationAction::Renotify => {
+                        let lang = app_handle.state::<LanguageState>().0.lock().unwrap().clone();
+                        let title = get_task_display_title(&escalation.id, &escalation.title, &lang);
+                        let _ = app_handle.notification()
+                            .builder()
+                            .title(title)
+                            .body(get_unanswered_text(escalation.unanswered_seconds / 60, &lang))
+                            .show();
                     }
+                    EscalationAction::Sound => play_notification_sound(),
+                    // 锁屏界面由前端负责
+                    EscalationAction::LockScreen => {}
                 }
+                let _ = app_handle.emit("task-escalated", escalation);
+            }
 
-                // Self-Healing
-                if let Ok(monitors) = app_handle.available_monitors() {
-                    let mut covered_indices = HashSet::new();
-                    
-                    if let Some(main_win) = app_handle.get_webview_window("main") {
-                        if let Ok(pos) = main_win.outer_position() {
-                            for (i, m) in monitors.iter().enumerate() {
-                                if m.position().x == pos.x && m.position().y == pos.y {
-                                    covered_indices.insert(i);
-                                    break;
-                                }
-                            }
-                        }
-                    }
-
-                    for label in &windows {
-                        if let Some(slave) = app_handle.get_webview_window(label) {
-                            if let Ok(pos) = slave.outer_position() {
-                                for (i, m) in monitors.iter().enumerate() {
-                                    if m.position().x == pos.x && m.position().y == pos.y {
-                                        covered_indices.insert(i);
-                                    }
-                                }
-                            }
-                        }
-                    }
+            // 用户自然离开达到阈值的任务已自动重置
+            if let Some(natural_break) = output.natural_break {
+                let _ = app_handle.emit("natural-break", natural_break);
+            }
 
-                    for (i, m) in monitors.iter().enumerate() {
-                        if !covered_indices.contains(&i) {
-                            let label = format!("lock-slave-{}", i);
-                            if let Some(win) = app_handle.get_webview_window(&label) {
-                                let _ = win.set_position(m.position().clone());
-                                let _ = win.set_size(tauri::Size::Physical(m.size().clone()));
-                                let _ = win.set_fullscreen(true);
-                            } else {
-                                if let Some(new_label) = create_slave_window(&app_handle, m, args.as_ref(), i) {
-                                    guard.windows.push(new_label);
-                                }
-                            }
-                        }
-                    }
-                }
+            // 发送空闲状态更新（只在状态变化时发送）
+            if let Some(status) = output.idle_status {
+                let _ = app_handle.emit("idle-status-changed", status);
             }
+
+            // 发送倒计时更新
+            let _ = app_handle.emit("countdown-update", output.countdowns);
         }
     });
 }
@@ -848,6 +572,63 @@ fn get_settings_path() -> PathBuf {
     config_dir.join("desk-reminder").join("settings.json")
 }
 
+fn get_timer_progress_path() -> PathBuf {
+    get_settings_path().with_file_name("timers.json")
+}
+
+/// 紧急解锁设置由后端保存，不放在前端可以直接覆盖的 settings.json 中
+fn get_emergency_config_path() -> PathBuf {
+    get_settings_path().with_file_name("emergency_unlock.json")
+}
+
+/// 紧急解锁日志，每行一条 JSON 记录
+fn get_emergency_log_path() -> PathBuf {
+    get_settings_path().with_file_name("emergency_unlock.log")
+}
+
+fn load_emergency_config() -> EmergencyUnlockConfig {
+    fs::read_to_string(get_emergency_config_path()).ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn append_emergency_log(record: &EmergencyUnlockRecord) {
+    use std::io::Write;
+
+    let path = get_emergency_log_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let (Ok(mut file), Ok(line)) = (fs::OpenOptions::new().create(true).append(true).open(path), serde_json::to_string(record)) {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+/// 计时进度保存间隔（秒）
+const TIMER_PROGRESS_SAVE_INTERVAL: u64 = 15;
+
+fn save_timer_progress() {
+    let Some(snapshot) = get_timer_state().lock().unwrap().snapshot(CLOCK.now()) else {
+        return;
+    };
+    let path = get_timer_progress_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    // 先写临时文件再替换，写到一半崩溃时保留上一次的进度
+    if let Ok(json) = serde_json::to_string(&snapshot) {
+        let tmp = path.with_extension("json.tmp");
+        if fs::write(&tmp, json).is_ok() {
+            let _ = fs::rename(&tmp, &path);
+        }
+    }
+}
+
+fn load_timer_progress() -> Option<TimerSnapshot> {
+    let json = fs::read_to_string(get_timer_progress_path()).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
 #[tauri::command]
 fn load_settings() -> String {
     let path = get_settings_path();
@@ -928,7 +709,11 @@ fn show_main_window(window: tauri::Window) {
 }
 
 #[tauri::command]
-fn hide_main_window(window: tauri::Window) {
+fn hide_main_window(window: tauri::Window, lock_state: State<LockState>) {
+    // 锁屏期间主窗口即锁屏界面，不能隐藏
+    if lock_state.0.lock().unwrap().session.is_some() {
+        return;
+    }
     let _ = window.hide();
 }
 
@@ -968,26 +753,222 @@ fn update_tray_language(app: AppHandle, lang_state: State<LanguageState>, langua
     }
 }
 
-fn create_slave_window(app: &AppHandle, monitor: &tauri::Monitor, task: Option<&LockTaskArgs>, index: usize) -> Option<String> {
-    let label = format!("lock-slave-{}", index);
-    
-    let mut url_str = String::from("index.html?mode=lock_slave");
-    if let Some(t) = task {
-         let encoded: String = form_urlencoded::Serializer::new(String::new())
-            .append_pair("title", &t.title)
-            .append_pair("desc", &t.desc)
-            .append_pair("duration", &t.duration.to_string())
-            .append_pair("icon", &t.icon)
-            .append_pair("strict_mode", &t.strict_mode.to_string())
-            .append_pair("allow_strict_snooze", &t.allow_strict_snooze.to_string())
-            .append_pair("max_snooze_count", &t.max_snooze_count.to_string())
-            .append_pair("snooze_minutes", &t.snooze_minutes.to_string())
-            .append_pair("current_snooze_count", &t.current_snooze_count.to_string())
-            .finish();
-         url_str = format!("index.html?mode=lock_slave&{}", encoded);
+// ============= 锁屏看门狗 (Watchdog) =============
+// 确保锁屏窗口始终置顶且聚焦，防止被最小化；同时补上缺少的从属窗口
+fn lock_watchdog(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        if !window.is_visible().unwrap_or(false) { let _ = window.show(); }
+        let _ = window.unminimize();
+        if !window.is_focused().unwrap_or(false) { let _ = window.set_focus(); }
+        let _ = window.set_always_on_top(true);
     }
 
-    if let Ok(slave) = WebviewWindowBuilder::new(app, &label, WebviewUrl::App(PathBuf::from(url_str)))
+    let labels = app.state::<LockState>().0.lock().unwrap().monitors.labels();
+    for label in &labels {
+        if let Some(window) = app.get_webview_window(label) {
+            if !window.is_visible().unwrap_or(false) { let _ = window.show(); }
+            if !window.is_focused().unwrap_or(false) { let _ = window.set_focus(); }
+            let _ = window.set_always_on_top(true);
+        }
+    }
+
+    let input = app.state::<LockState>().0.lock().unwrap()
+        .session.as_ref()
+        .map(|session| (session.holds_input(CLOCK.now().instant), session.input_grabbed()));
+    match input {
+        // 定期重新抓取，恢复被其他程序打断的抓取
+        Some((true, _)) => grab_lock_input(app),
+        // 倒计时结束后释放，等待确认时不再限制输入
+        Some((false, true)) => release_lock_input(app),
+        _ => {}
+    }
+
+    sync_lock_monitors(app);
+}
+
+/// 监听显示器接入、断开和调整排列，锁屏期间立即同步从属窗口；接入副屏时主窗口不会收到任何事件。
+/// 信号在主线程触发，创建窗口需要等待主线程，因此在新线程中同步
+#[cfg(target_os = "linux")]
+fn watch_monitors(app: AppHandle) {
+    let Some(screen) = gtk::gdk::Screen::default() else {
+        return;
+    };
+    screen.connect_monitors_changed(move |_| {
+        if app.state::<LockState>().0.lock().unwrap().session.is_some() {
+            let app = app.clone();
+            thread::spawn(move || sync_lock_monitors(&app));
+        }
+    });
+}
+
+/// 锁屏会话编号，输入抓取的失效保护只处理自己所属的会话
+static LOCK_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 输入抓取的失效保护在休息结束后再等待的秒数，定时器线程卡住时也能释放抓取
+const INPUT_GRAB_FAILSAFE_MARGIN: u64 = 30;
+
+/// 失效保护每轮等待主线程响应心跳的秒数
+const INPUT_GRAB_HEARTBEAT_SECONDS: u64 = 5;
+
+/// 连续错过多少次心跳视为主循环卡住，主线程只是暂时变慢时不应断开连接
+const INPUT_GRAB_MAX_MISSED_HEARTBEATS: u32 = 6;
+
+/// 持有输入抓取的 X 窗口 ID，0 为没有抓取；由主线程在抓取和释放后更新
+#[cfg(target_os = "linux")]
+static GRABBED_WINDOW: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+/// 严格模式的休息期间抓取键盘和鼠标（仅 Linux X11）。GTK 的显示连接只能在主线程使用；
+/// 执行前再次检查会话，锁屏结束后才执行的抓取请求会被忽略
+fn grab_lock_input(app: &AppHandle) {
+    #[cfg(target_os = "linux")]
+    {
+        let handle = app.clone();
+        let _ = app.run_on_main_thread(move || {
+            let lock_state = handle.state::<LockState>();
+            let mut guard = lock_state.0.lock().unwrap();
+            let Some(session) = guard.session.as_mut() else {
+                return;
+            };
+            if !session.holds_input(CLOCK.now().instant) {
+                return;
+            }
+            let window = handle.get_webview_window("main").and_then(|window| window.gtk_window().ok());
+            // 抓取可能只成功一项，只要在真实的 X 窗口上尝试过就记录，失效保护据此释放
+            if let Some(xid) = window.as_ref().and_then(input_grab::window_xid) {
+                GRABBED_WINDOW.store(xid, Ordering::SeqCst);
+            }
+            let result = window
+                .ok_or(input_grab::GrabError::Unsupported)
+                .and_then(|window| input_grab::grab_window(&window));
+            session.set_input_grab(result.map_err(|e| e.to_string()));
+        });
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = app;
+}
+
+/// 释放键盘和鼠标，不依赖锁屏状态，其他线程崩溃导致锁失效时也能释放
+fn release_lock_input(app: &AppHandle) {
+    #[cfg(target_os = "linux")]
+    {
+        let handle = app.clone();
+        let _ = app.run_on_main_thread(move || {
+            if let Some(window) = handle.get_webview_window("main").and_then(|window| window.gtk_window().ok()) {
+                input_grab::release_window(&window);
+            }
+            GRABBED_WINDOW.store(0, Ordering::SeqCst);
+            if let Ok(mut guard) = handle.state::<LockState>().0.lock() {
+                if let Some(session) = guard.session.as_mut() {
+                    session.set_input_released();
+                }
+            }
+        });
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = app;
+}
+
+/// 释放请求需要主线程执行。主线程连续错过多次心跳且抓取仍未释放时，
+/// 在单独的连接上断开本程序的 X 连接，由 X 服务器释放抓取；程序随之退出，但桌面不会一直被锁住
+fn force_release_if_main_loop_stuck(app: &AppHandle) {
+    #[cfg(target_os = "linux")]
+    {
+        // 主线程按顺序执行任务，响应心跳时之前的释放请求已经执行
+        let stuck = input_grab::stuck_grab(
+            |tx| {
+                let _ = app.run_on_main_thread(move || {
+                    let _ = tx.send(());
+                });
+            },
+            || GRABBED_WINDOW.load(Ordering::SeqCst),
+            Duration::from_secs(INPUT_GRAB_HEARTBEAT_SECONDS),
+            INPUT_GRAB_MAX_MISSED_HEARTBEATS,
+        );
+        if let Some(window) = stuck {
+            if let Err(e) = input_grab::linux::kill_client(None, window) {
+                let _ = app.emit("input-grab-error", e);
+            }
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = app;
+}
+
+fn monitor_layout(monitor: &tauri::Monitor) -> MonitorLayout {
+    MonitorLayout {
+        name: monitor.name().cloned(),
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width,
+        height: monitor.size().height,
+    }
+}
+
+/// 按当前的显示器布局创建、移动或关闭从属锁屏窗口，主窗口所在的显示器不需要从属窗口。
+/// Linux 上显示器接入、断开或调整排列时由 GDK 的 monitors-changed 信号立即同步；
+/// 其他平台只有主窗口所在的显示器变化时才有移动、缩放事件，其余变化由看门狗每秒检查
+fn sync_lock_monitors(app: &AppHandle) {
+    let Some(main) = app.get_webview_window("main") else {
+        return;
+    };
+    let monitors: Vec<MonitorLayout> = main.available_monitors().unwrap_or_default().iter().map(monitor_layout).collect();
+    // 获取显示器失败时保持现状，避免误关所有从属窗口
+    if monitors.is_empty() {
+        return;
+    }
+    let main_monitor = main.current_monitor().ok().flatten().map(|m| monitor_layout(&m));
+
+    let lock_state = app.state::<LockState>();
+    let changes = {
+        let mut guard = lock_state.0.lock().unwrap();
+        if guard.session.is_none() {
+            return;
+        }
+        guard.monitors.sync(&monitors, main_monitor.as_ref())
+    };
+
+    // 创建窗口需要主线程处理，操作窗口时不能持有锁屏状态的锁，否则主线程上的命令会死锁
+    for change in changes {
+        match change {
+            SlaveChange::Create { label, monitor } => {
+                let created = create_slave_window(app, &label, &monitor);
+                let mut guard = lock_state.0.lock().unwrap();
+                if !created {
+                    guard.monitors.forget(&label);
+                } else if guard.session.is_none() || !guard.monitors.contains(&label) {
+                    // 创建期间锁屏已结束
+                    drop(guard);
+                    if let Some(window) = app.get_webview_window(&label) {
+                        let _ = window.close();
+                    }
+                }
+            }
+            SlaveChange::Move { label, monitor } => {
+                if let Some(window) = app.get_webview_window(&label) {
+                    place_slave_window(&window, &monitor);
+                }
+            }
+            SlaveChange::Close { label } => {
+                if let Some(window) = app.get_webview_window(&label) {
+                    let _ = window.close();
+                }
+            }
+        }
+    }
+}
+
+/// 全屏窗口需先退出全屏才能移动到另一台显示器
+fn place_slave_window(window: &tauri::WebviewWindow, monitor: &MonitorLayout) {
+    let _ = window.set_fullscreen(false);
+    let _ = window.set_position(tauri::PhysicalPosition::new(monitor.x, monitor.y));
+    let _ = window.set_size(tauri::PhysicalSize::new(monitor.width, monitor.height));
+    let _ = window.set_fullscreen(true);
+}
+
+/// 从属屏幕的锁屏窗口不携带锁屏参数，加载后通过 get_lock_session 读取会话，
+/// 之后跟随 lock-tick 事件更新，因此各屏幕显示同一个会话，重建窗口也不会使用过期的状态
+fn create_slave_window(app: &AppHandle, label: &str, monitor: &MonitorLayout) -> bool {
+    let Ok(slave) = WebviewWindowBuilder::new(app, label, WebviewUrl::App(PathBuf::from("index.html?mode=lock_slave")))
         .title("Lock Screen")
         .always_on_top(true)
         .closable(false)
@@ -996,21 +977,94 @@ fn create_slave_window(app: &AppHandle, monitor: &tauri::Monitor, task: Option<&
         .resizable(false)
         .skip_taskbar(true)
         .visible(false)
-        .build() {
-            
-        let _ = slave.set_position(monitor.position().clone());
-        let _ = slave.set_size(tauri::Size::Physical(monitor.size().clone()));
-        let _ = slave.show();
-        let _ = slave.set_focus();
-        let _ = slave.set_fullscreen(true);
-        Some(label)
-    } else {
-        None
+        .build() else {
+        return false;
+    };
+    place_slave_window(&slave, monitor);
+    let _ = slave.show();
+    let _ = slave.set_focus();
+    true
+}
+
+/// 当前锁屏会话的状态，附带本次提醒（包括合并的任务）能否推迟
+fn lock_state_of(session: &LockSession) -> LockTick {
+    let now = CLOCK.now();
+    let (snooze_options, snooze_blocked) = get_timer_state().lock().unwrap().snooze_state(&session.request().task_id, now);
+    session.state(now.instant, snooze_options, snooze_blocked)
+}
+
+fn lock_tick(app: &AppHandle) -> Option<LockTick> {
+    let lock_state = app.state::<LockState>();
+    let guard = lock_state.0.lock().unwrap();
+    guard.session.as_ref().map(lock_state_of)
+}
+
+/// 恢复主窗口并关闭其他屏幕上的锁屏窗口
+fn close_lock_windows(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_fullscreen(false);
+        let _ = window.set_always_on_top(false);
+        let _ = window.set_closable(true);
+        let _ = window.set_minimizable(true);
+    }
+
+    let windows = app.state::<LockState>().0.lock().unwrap().monitors.take_labels();
+    for label in windows {
+        if let Some(w) = app.get_webview_window(&label) {
+            let _ = w.close();
+        }
     }
 }
 
+/// 结束锁屏会话：恢复计时，完成休息时重置任务，关闭锁屏窗口并通知所有窗口
+fn finish_lock_session(app: &AppHandle, reason: LockEndReason) {
+    let Some(session) = app.state::<LockState>().0.lock().unwrap().session.take() else {
+        return;
+    };
+    LOCK_GENERATION.fetch_add(1, Ordering::SeqCst);
+    if session.input_grabbed() {
+        release_lock_input(app);
+    }
+    let task_ids = session.request().task_ids.clone();
+    let now = CLOCK.now();
+    let reset_ids = {
+        let mut state = get_timer_state().lock().unwrap();
+        // 先补偿锁屏时间再重置任务，避免重置后的任务被再次补偿
+        state.set_lock_screen_active(false, now);
+        match reason {
+            LockEndReason::Snoozed => Vec::new(),
+            LockEndReason::Completed => state.complete_break(&task_ids, now),
+            LockEndReason::Unlocked | LockEndReason::Emergency => state.skip_break(&task_ids, now),
+        }
+    };
+
+    close_lock_windows(app);
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.hide();
+    }
+    let _ = app.emit("lock-ended", LockEndedPayload { reason, task_ids, reset_ids });
+}
+
+/// 开始锁屏会话，严格模式取自当前设置，会话期间不再变化
 #[tauri::command]
-async fn enter_lock_mode(app: tauri::AppHandle, window: tauri::Window, state: State<'_, LockState>, task: Option<LockTaskArgs>) -> Result<(), String> {
+async fn enter_lock_mode(app: tauri::AppHandle, window: tauri::Window, state: State<'_, LockState>, task: LockRequest) -> Result<(), String> {
+    let tick = {
+        let mut state_guard = state.0.lock().unwrap();
+        if state_guard.session.is_some() {
+            return Err("a lock session is already active".to_string());
+        }
+        let now = CLOCK.now();
+        let strict_mode = {
+            let mut timer = get_timer_state().lock().unwrap();
+            timer.set_lock_screen_active(true, now);
+            timer.is_strict_mode()
+        };
+        let session = LockSession::start(task, strict_mode, load_emergency_config(), now.instant);
+        let tick = lock_state_of(&session);
+        state_guard.session = Some(session);
+        tick
+    };
+
     let _ = window.unminimize();
     let _ = window.show();
     let _ = window.set_fullscreen(true);
@@ -1019,46 +1073,161 @@ async fn enter_lock_mode(app: tauri::AppHandle, window: tauri::Window, state: St
     let _ = window.set_minimizable(false);
     let _ = window.set_focus();
 
-    let monitors = window.available_monitors().unwrap_or_default();
-    let current_monitor = window.current_monitor().unwrap_or(None);
-    
-    let mut created_windows = Vec::new();
-    
-    for (i, m) in monitors.iter().enumerate() {
-        if let Some(ref cm) = current_monitor {
-             // Basic position check to assume it's the same monitor
-             if m.position().x == cm.position().x && m.position().y == cm.position().y {
-                 continue;
-             }
-        }
-
-        if let Some(label) = create_slave_window(&app, m, task.as_ref(), i) {
-            created_windows.push(label);
-        }
+    sync_lock_monitors(&app);
+
+    // 严格模式下抓取输入，看门狗定期重试；失效保护在定时器线程或主循环卡住时仍会释放抓取
+    if tick.strict_mode {
+        grab_lock_input(&app);
+        let generation = LOCK_GENERATION.load(Ordering::SeqCst);
+        let failsafe_after = Duration::from_secs(tick.duration + INPUT_GRAB_FAILSAFE_MARGIN);
+        let handle = app.clone();
+        thread::spawn(move || {
+            thread::sleep(failsafe_after);
+            if LOCK_GENERATION.load(Ordering::SeqCst) == generation {
+                release_lock_input(&handle);
+            }
+            force_release_if_main_loop_stuck(&handle);
+        });
     }
-    
-    let mut state_guard = state.0.lock().unwrap();
-    state_guard.windows.extend(created_windows);
-    state_guard.args = task;
+    let _ = app.emit("lock-tick", tick);
 
     Ok(())
 }
 
+/// 当前锁屏会话的状态，网页刷新后用于恢复锁屏界面
 #[tauri::command]
-fn exit_lock_mode(app: tauri::AppHandle, window: tauri::Window, state: State<LockState>) {
-    let _ = window.set_fullscreen(false);
-    let _ = window.set_always_on_top(false);
-    let _ = window.set_closable(true);
-    let _ = window.set_minimizable(true);
+fn get_lock_session(app: AppHandle) -> Option<LockTick> {
+    lock_tick(&app)
+}
 
-    let mut state_guard = state.0.lock().unwrap();
-    for label in state_guard.windows.iter() {
-        if let Some(w) = app.get_webview_window(label) {
-            let _ = w.close();
+/// 倒计时结束后确认完成休息
+#[tauri::command]
+fn lock_confirm(app: AppHandle) -> Result<(), String> {
+    let reason = match &app.state::<LockState>().0.lock().unwrap().session {
+        Some(session) => session.confirm(CLOCK.now().instant),
+        None => Err(LockRefusal::NoSession),
+    }.map_err(|e| e.to_string())?;
+    finish_lock_session(&app, reason);
+    Ok(())
+}
+
+/// 长按提前解锁，严格模式下拒绝
+#[tauri::command]
+fn lock_unlock(app: AppHandle) -> Result<(), String> {
+    let reason = match &app.state::<LockState>().0.lock().unwrap().session {
+        Some(session) => session.unlock(CLOCK.now().instant),
+        None => Err(LockRefusal::NoSession),
+    }.map_err(|e| e.to_string())?;
+    finish_lock_session(&app, reason);
+    Ok(())
+}
+
+/// 推迟本次休息，按主任务的推迟规则检查
+#[tauri::command]
+fn lock_snooze(app: AppHandle, minutes: u64) -> Result<Vec<String>, String> {
+    let task_id = app.state::<LockState>().0.lock().unwrap()
+        .session.as_ref()
+        .map(|session| session.request().task_id.clone())
+        .ok_or_else(|| LockRefusal::NoSession.to_string())?;
+    let snoozed = {
+        let now = CLOCK.now();
+        let mut state = get_timer_state().lock().unwrap();
+        // 先补偿锁屏时间再推迟，否则结束锁屏时的补偿会加到新的推迟时长上
+        state.set_lock_screen_active(false, now);
+        let snoozed = state.snooze_task(&task_id, minutes, now);
+        if snoozed.is_err() {
+            state.set_lock_screen_active(true, now);
+        }
+        snoozed.map_err(|e| e.to_string())?
+    };
+    finish_lock_session(&app, LockEndReason::Snoozed);
+    Ok(snoozed)
+}
+
+/// 紧急解锁需要输入的随机句子
+#[tauri::command]
+fn lock_emergency_challenge(app: AppHandle) -> Result<String, String> {
+    let lock_state = app.state::<LockState>();
+    let mut guard = lock_state.0.lock().unwrap();
+    let session = guard.session.as_mut().ok_or_else(|| LockRefusal::NoSession.to_string())?;
+    session.emergency_challenge().map_err(|e| e.to_string())
+}
+
+/// 紧急解锁，由后端校验 PIN、口令或随机句子，成功后记录到日志。
+/// 哈希需要多次迭代，异步执行避免阻塞主线程
+#[tauri::command]
+async fn lock_emergency_unlock(app: AppHandle, answer: String) -> Result<(), String> {
+    let attempt = match &app.state::<LockState>().0.lock().unwrap().session {
+        Some(session) => session.emergency_attempt(),
+        None => Err(LockRefusal::NoSession),
+    }.map_err(|e| e.to_string())?;
+    // 哈希计算较慢，核对期间不占用锁屏状态，倒计时和看门狗照常运行
+    let accepted = attempt.verify(&answer);
+    let record = {
+        let lock_state = app.state::<LockState>();
+        let mut guard = lock_state.0.lock().unwrap();
+        let session = guard.session.as_mut().ok_or_else(|| LockRefusal::NoSession.to_string())?;
+        let result = session.finish_emergency_attempt(&attempt, accepted);
+        // 失败次数随锁屏状态广播
+        if result.is_err() {
+            let _ = app.emit("lock-tick", lock_state_of(session));
+        }
+        let method = result.map_err(|e| e.to_string())?;
+        EmergencyUnlockRecord {
+            time: Local::now().to_rfc3339(),
+            method,
+            task_ids: session.request().task_ids.clone(),
+            remaining: session.remaining(CLOCK.now().instant),
         }
+    };
+    append_emergency_log(&record);
+    finish_lock_session(&app, LockEndReason::Emergency);
+    Ok(())
+}
+
+/// 当前的紧急解锁方式，不返回哈希
+#[tauri::command]
+fn get_emergency_unlock() -> Option<EmergencyMethod> {
+    load_emergency_config().method
+}
+
+/// 设置紧急解锁；锁屏会话进行中时拒绝修改，method 为 None 时关闭
+#[tauri::command]
+async fn set_emergency_unlock(lock_state: State<'_, LockState>, method: Option<EmergencyMethod>, secret: Option<String>) -> Result<(), String> {
+    if lock_state.0.lock().unwrap().session.is_some() {
+        return Err("cannot change emergency unlock during a lock session".to_string());
     }
-    state_guard.windows.clear();
-    state_guard.args = None;
+    let config = match method {
+        Some(method) => EmergencyUnlockConfig::with_secret(method, secret.as_deref().unwrap_or_default())
+            .map_err(|e| e.to_string())?,
+        None => EmergencyUnlockConfig::default(),
+    };
+    let path = get_emergency_config_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/// 紧急解锁日志，最近的在前
+#[tauri::command]
+fn get_emergency_unlock_log(limit: usize) -> Vec<EmergencyUnlockRecord> {
+    let log = fs::read_to_string(get_emergency_log_path()).unwrap_or_default();
+    log.lines().rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect()
+}
+
+/// 只恢复窗口状态；锁屏会话进行中时拒绝，结束锁屏需通过会话
+#[tauri::command]
+fn exit_lock_mode(app: tauri::AppHandle, state: State<LockState>) -> Result<(), String> {
+    if state.0.lock().unwrap().session.is_some() {
+        return Err("a lock session is active".to_string());
+    }
+    close_lock_windows(&app);
+    Ok(())
 }
 
 pub fn run() {
@@ -1083,20 +1252,39 @@ pub fn run() {
             update_tray_language,
             enter_lock_mode,
             exit_lock_mode,
+            get_lock_session,
+            lock_confirm,
+            lock_unlock,
+            lock_snooze,
+            lock_emergency_challenge,
+            lock_emergency_unlock,
+            get_emergency_unlock,
+            set_emergency_unlock,
+            get_emergency_unlock_log,
             sync_tasks,
             timer_pause,
+            timer_pause_for,
             timer_resume,
             timer_reset_task,
+            timer_complete_break,
             timer_reset_all,
             timer_snooze_task,
+            timer_set_strict_mode,
             get_countdowns,
             timer_set_system_locked,
-            timer_set_lock_screen_active,
             set_idle_threshold,
             get_idle_threshold,
+            set_offline_break_threshold,
+            get_idle_backend,
+            set_merge_window,
+            set_quiet_hours,
+            set_fullscreen_policy,
+            set_call_detection,
+            set_app_rules,
+            get_focused_app,
         ])
         .manage(TrayState(Mutex::new(None)))
-        .manage(LockState(Mutex::new(LockStateInner { windows: Vec::new(), args: None })))
+        .manage(LockState(Mutex::new(LockStateInner { monitors: MonitorTracker::default(), session: None })))
         .manage(PauseMenuState(Mutex::new(None)))
         .manage(LanguageState(Mutex::new("zh-CN".to_string())))
         .setup(|app| {
@@ -1124,19 +1312,16 @@ pub fn run() {
                         let _ = app.emit("reset-all-tasks", ());
                     } else if id_str == "pause" {
                         let _ = app.emit("toggle-pause", ());
+                    } else if let Some((_, _, minutes)) = PAUSE_FOR_MENU.iter().find(|(id, _, _)| *id == id_str) {
+                        let now = CLOCK.now().wall;
+                        let until = match minutes {
+                            Some(minutes) => now + chrono::Duration::minutes(*minutes),
+                            None => tomorrow_start(now),
+                        };
+                        pause_timer_until(app, until);
                     } else if id_str.starts_with("reset_task_") {
                         let task_id = id_str.trim_start_matches("reset_task_");
-                        let mut state = get_timer_state().lock().unwrap();
-                        let now = Instant::now();
-                        if let Some(timer) = state.tasks.get_mut(task_id) {
-                            timer.reset_time = now;
-                            timer.triggered = false;
-                            timer.snoozed = false;
-                            timer.snooze_count = 0;
-                            if timer.disabled_at.is_some() {
-                                timer.disabled_at = Some(now);
-                            }
-                        }
+                        get_timer_state().lock().unwrap().reset_task(task_id, CLOCK.now());
                     }
                 })
                 .on_tray_icon_event(|tray, event| {
@@ -1158,11 +1343,19 @@ pub fn run() {
             *app.state::<TrayState>().0.lock().unwrap() = Some(tray);
             *app.state::<PauseMenuState>().0.lock().unwrap() = Some(pause);
 
+            // 恢复上次保存的计时进度，首次同步任务时生效
+            if let Some(snapshot) = load_timer_progress() {
+                get_timer_state().lock().unwrap().restore(snapshot);
+            }
+
             // 启动后端定时器线程
             start_timer_thread(app.handle().clone());
 
-            #[cfg(target_os = "windows")]
+            #[cfg(any(target_os = "windows", target_os = "linux"))]
             start_session_monitor(app.handle().clone());
+
+            #[cfg(target_os = "linux")]
+            watch_monitors(app.handle().clone());
             
             Ok(())
         })
@@ -1176,10 +1369,30 @@ pub fn run() {
                     let _ = window.hide();
                 }
             }
+
+            match event {
+                // 从属锁屏窗口意外销毁时移除对应关系，看门狗会重新创建
+                WindowEvent::Destroyed if window.label().starts_with("lock-slave-") => {
+                    window.state::<LockState>().0.lock().unwrap().monitors.forget(window.label());
+                }
+                // 主窗口所在的显示器变化时全屏的主窗口会被移动或缩放，立即同步从属窗口。
+                // 事件在主线程处理，创建窗口需要等待主线程，因此在新线程中同步
+                WindowEvent::Moved(_) | WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. }
+                    if window.label() == "main" && window.state::<LockState>().0.lock().unwrap().session.is_some() =>
+                {
+                    let app = window.app_handle().clone();
+                    thread::spawn(move || sync_lock_monitors(&app));
+                }
+                _ => {}
+            }
         })
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|_app_handle, _event| {
+            if let tauri::RunEvent::Exit = _event {
+                save_timer_progress();
+            }
+
             #[cfg(target_os = "macos")]
             if let tauri::RunEvent::Reopen { .. } = _event {
                 if let Some(window) = _app_handle.get_webview_window("main") {
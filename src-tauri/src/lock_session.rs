@@ -0,0 +1,417 @@
+use std::time::{Duration, Instant};
+
+use crate::emergency::{self, EmergencyMethod, EmergencyUnlockConfig};
+use crate::scheduler::SnoozeRefusal;
+
+// ============= 锁屏会话 =============
+//
+// 休息倒计时、严格模式和结束流程都由后端的锁屏会话负责，前端只负责显示。
+// 这样网页刷新或在开发者工具中调用命令都无法绕过严格模式。
+// 定时器线程每秒推进会话并向所有窗口广播 lock-tick 事件。
+
+/// 每次锁屏允许的紧急解锁失败次数
+const MAX_EMERGENCY_ATTEMPTS: u32 = 5;
+
+/// 前端请求锁屏时提供的提醒内容
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct LockRequest {
+    pub title: String,
+    pub desc: String,
+    pub icon: String,
+    pub duration: u64,  // 休息秒数
+    #[serde(default)]
+    pub task_id: String,  // 提醒的主任务，推迟按该任务的规则由后端判断
+    #[serde(default)]
+    pub task_ids: Vec<String>,  // 本次锁屏包含的全部任务，缺省只有主任务
+    #[serde(default = "default_auto_unlock")]
+    pub auto_unlock: bool,  // 倒计时结束后自动解锁，否则等待用户确认
+}
+
+fn default_auto_unlock() -> bool {
+    true
+}
+
+/// 广播给所有窗口的锁屏状态
+#[derive(Clone, serde::Serialize)]
+pub struct LockTick {
+    pub task_id: String,
+    pub task_ids: Vec<String>,
+    pub title: String,
+    pub desc: String,
+    pub icon: String,
+    pub duration: u64,
+    pub remaining: u64,
+    pub waiting_confirm: bool,  // 倒计时已结束，等待用户确认
+    pub strict_mode: bool,
+    pub snooze_options: Vec<u64>,
+    pub snooze_blocked: Option<SnoozeRefusal>,
+    pub input_grab_error: Option<String>,  // 严格模式下抓取键盘和鼠标失败的原因
+    pub emergency_unlock: Option<EmergencyMethod>,  // 可用的紧急解锁方式，None 为未启用
+    pub emergency_attempts_left: u32,
+}
+
+/// 锁屏结束的方式
+#[derive(Clone, Copy, serde::Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LockEndReason {
+    /// 倒计时结束（自动解锁或用户确认）
+    Completed,
+    /// 非严格模式下长按提前解锁，不算完成休息
+    Unlocked,
+    /// 推迟了本次休息
+    Snoozed,
+    /// 通过 PIN、口令或随机句子紧急解锁，与提前解锁相同，不算完成休息
+    Emergency,
+}
+
+/// 锁屏结束时发送
+#[derive(Clone, serde::Serialize)]
+pub struct LockEndedPayload {
+    pub reason: LockEndReason,
+    pub task_ids: Vec<String>,  // 本次锁屏包含的任务
+    pub reset_ids: Vec<String>,  // 完成休息后重置的任务，包含被抵扣的任务；推迟时为空
+}
+
+/// 拒绝结束锁屏的原因
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockRefusal {
+    NoSession,
+    StrictMode,
+    BreakNotOver,
+    EmergencyUnavailable,
+    EmergencyRejected,
+    TooManyAttempts,
+}
+
+impl std::fmt::Display for LockRefusal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::NoSession => "no active lock session",
+            Self::StrictMode => "early unlock is disabled in strict mode",
+            Self::BreakNotOver => "the break is not over yet",
+            Self::EmergencyUnavailable => "emergency unlock is not enabled",
+            Self::EmergencyRejected => "the emergency unlock answer is incorrect",
+            Self::TooManyAttempts => "too many failed emergency unlock attempts",
+        };
+        f.write_str(text)
+    }
+}
+
+/// 每秒推进的结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockStep {
+    Running,
+    WaitingConfirm,
+    Completed,
+}
+
+/// 一次紧急解锁需要核对的数据。PIN 和口令的哈希计算较慢，取出后在锁屏状态的锁之外核对
+pub struct EmergencyAttempt {
+    method: EmergencyMethod,
+    config: EmergencyUnlockConfig,
+    challenge: Option<String>,
+}
+
+impl EmergencyAttempt {
+    pub fn verify(&self, answer: &str) -> bool {
+        match self.method {
+            EmergencyMethod::Pin | EmergencyMethod::Passphrase => self.config.verify_secret(answer),
+            EmergencyMethod::Challenge => self.challenge.as_deref()
+                .is_some_and(|expected| emergency::challenge_matches(expected, answer)),
+        }
+    }
+}
+
+pub struct LockSession {
+    request: LockRequest,
+    strict_mode: bool,  // 开始时的严格模式，会话期间不再变化
+    started_at: Instant,
+    waiting_confirm: bool,
+    input_grabbed: bool,
+    input_grab_error: Option<String>,
+    emergency: EmergencyUnlockConfig,  // 开始时的紧急解锁设置
+    challenge: Option<String>,  // 当前需要输入的随机句子
+    emergency_failures: u32,
+}
+
+impl LockSession {
+    pub fn start(mut request: LockRequest, strict_mode: bool, emergency: EmergencyUnlockConfig, now: Instant) -> Self {
+        if request.task_ids.is_empty() {
+            request.task_ids = vec![request.task_id.clone()];
+        }
+        Self {
+            request,
+            strict_mode,
+            started_at: now,
+            waiting_confirm: false,
+            input_grabbed: false,
+            input_grab_error: None,
+            emergency,
+            challenge: None,
+            emergency_failures: 0,
+        }
+    }
+
+    pub fn request(&self) -> &LockRequest {
+        &self.request
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict_mode
+    }
+
+    pub fn remaining(&self, now: Instant) -> u64 {
+        let end = self.started_at + Duration::from_secs(self.request.duration);
+        end.saturating_duration_since(now).as_secs()
+    }
+
+    /// 严格模式的休息倒计时期间需要抓取键盘和鼠标，倒计时结束后即使等待确认也应释放
+    pub fn holds_input(&self, now: Instant) -> bool {
+        self.strict_mode && self.remaining(now) > 0
+    }
+
+    pub fn input_grabbed(&self) -> bool {
+        self.input_grabbed
+    }
+
+    /// 记录抓取结果，失败原因随锁屏状态广播。抓取可能部分成功，失败时同样需要释放
+    pub fn set_input_grab(&mut self, result: Result<(), String>) {
+        self.input_grabbed = true;
+        self.input_grab_error = result.err();
+    }
+
+    pub fn set_input_released(&mut self) {
+        self.input_grabbed = false;
+    }
+
+    /// 推进倒计时：结束后自动解锁，或转入等待确认
+    pub fn tick(&mut self, now: Instant) -> LockStep {
+        if self.remaining(now) > 0 {
+            return LockStep::Running;
+        }
+        if self.request.auto_unlock {
+            return LockStep::Completed;
+        }
+        self.waiting_confirm = true;
+        LockStep::WaitingConfirm
+    }
+
+    /// 倒计时结束后确认完成休息
+    pub fn confirm(&self, now: Instant) -> Result<LockEndReason, LockRefusal> {
+        if self.remaining(now) > 0 {
+            return Err(LockRefusal::BreakNotOver);
+        }
+        Ok(LockEndReason::Completed)
+    }
+
+    /// 提前解锁，严格模式下拒绝
+    pub fn unlock(&self, now: Instant) -> Result<LockEndReason, LockRefusal> {
+        if self.remaining(now) == 0 {
+            return Ok(LockEndReason::Completed);
+        }
+        if self.strict_mode {
+            return Err(LockRefusal::StrictMode);
+        }
+        Ok(LockEndReason::Unlocked)
+    }
+
+    /// 本次锁屏需要输入的随机句子，同一句子在答错前保持不变
+    pub fn emergency_challenge(&mut self) -> Result<String, LockRefusal> {
+        if self.emergency.method != Some(EmergencyMethod::Challenge) {
+            return Err(LockRefusal::EmergencyUnavailable);
+        }
+        if self.emergency_failures >= MAX_EMERGENCY_ATTEMPTS {
+            return Err(LockRefusal::TooManyAttempts);
+        }
+        if self.challenge.is_none() {
+            self.challenge = emergency::challenge_sentence();
+        }
+        self.challenge.clone().ok_or(LockRefusal::EmergencyUnavailable)
+    }
+
+    /// 开始一次紧急解锁，严格模式下同样有效；答错达到上限后本次锁屏不再接受
+    pub fn emergency_attempt(&self) -> Result<EmergencyAttempt, LockRefusal> {
+        let method = self.emergency.method.ok_or(LockRefusal::EmergencyUnavailable)?;
+        if self.emergency_failures >= MAX_EMERGENCY_ATTEMPTS {
+            return Err(LockRefusal::TooManyAttempts);
+        }
+        Ok(EmergencyAttempt {
+            method,
+            config: self.emergency.clone(),
+            challenge: self.challenge.clone(),
+        })
+    }
+
+    /// 记录紧急解锁的核对结果；核对期间其他尝试可能已经用完次数，或答错后换了句子
+    pub fn finish_emergency_attempt(&mut self, attempt: &EmergencyAttempt, accepted: bool) -> Result<EmergencyMethod, LockRefusal> {
+        if self.emergency_failures >= MAX_EMERGENCY_ATTEMPTS {
+            return Err(LockRefusal::TooManyAttempts);
+        }
+        // 按旧句子核对的答案不再有效，已经换过句子，不计入失败次数
+        if attempt.challenge != self.challenge {
+            return Err(LockRefusal::EmergencyRejected);
+        }
+        if !accepted {
+            self.emergency_failures += 1;
+            // 答错后换一句，避免反复尝试同一句
+            self.challenge = None;
+            return Err(LockRefusal::EmergencyRejected);
+        }
+        Ok(attempt.method)
+    }
+
+    pub fn state(&self, now: Instant, snooze_options: Vec<u64>, snooze_blocked: Option<SnoozeRefusal>) -> LockTick {
+        let request = &self.request;
+        LockTick {
+            task_id: request.task_id.clone(),
+            task_ids: request.task_ids.clone(),
+            title: request.title.clone(),
+            desc: request.desc.clone(),
+            icon: request.icon.clone(),
+            duration: request.duration,
+            remaining: self.remaining(now),
+            waiting_confirm: self.waiting_confirm,
+            strict_mode: self.strict_mode,
+            snooze_options,
+            snooze_blocked,
+            input_grab_error: self.input_grab_error.clone(),
+            emergency_unlock: self.emergency.method,
+            emergency_attempts_left: MAX_EMERGENCY_ATTEMPTS.saturating_sub(self.emergency_failures),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(task_ids: &[&str], auto_unlock: bool) -> LockRequest {
+        LockRequest {
+            title: "Stand up".to_string(),
+            desc: String::new(),
+            icon: String::new(),
+            duration: 300,
+            task_id: "sit".to_string(),
+            task_ids: task_ids.iter().map(|id| id.to_string()).collect(),
+            auto_unlock,
+        }
+    }
+
+    fn session(strict_mode: bool, auto_unlock: bool, start: Instant) -> LockSession {
+        LockSession::start(request(&[], auto_unlock), strict_mode, EmergencyUnlockConfig::default(), start)
+    }
+
+    fn secs(start: Instant, seconds: u64) -> Instant {
+        start + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn empty_task_ids_fall_back_to_the_main_task() {
+        let start = Instant::now();
+        assert_eq!(session(false, true, start).request().task_ids, ["sit"]);
+        let merged = LockSession::start(request(&["sit", "eye"], true), false, EmergencyUnlockConfig::default(), start);
+        assert_eq!(merged.request().task_ids, ["sit", "eye"]);
+    }
+
+    #[test]
+    fn strict_mode_refuses_early_unlock() {
+        let start = Instant::now();
+        assert_eq!(session(true, true, start).unlock(secs(start, 100)), Err(LockRefusal::StrictMode));
+        assert_eq!(session(false, true, start).unlock(secs(start, 100)), Ok(LockEndReason::Unlocked));
+    }
+
+    #[test]
+    fn unlock_after_countdown_completes_the_break() {
+        let start = Instant::now();
+        assert_eq!(session(true, false, start).unlock(secs(start, 300)), Ok(LockEndReason::Completed));
+    }
+
+    #[test]
+    fn confirm_before_countdown_ends_is_refused() {
+        let start = Instant::now();
+        let lock = session(false, false, start);
+        assert_eq!(lock.confirm(secs(start, 299)), Err(LockRefusal::BreakNotOver));
+        assert_eq!(lock.confirm(secs(start, 300)), Ok(LockEndReason::Completed));
+    }
+
+    #[test]
+    fn tick_completes_or_waits_for_confirmation() {
+        let start = Instant::now();
+        let mut auto = session(false, true, start);
+        assert_eq!(auto.tick(secs(start, 299)), LockStep::Running);
+        assert_eq!(auto.tick(secs(start, 300)), LockStep::Completed);
+
+        let mut manual = session(false, false, start);
+        assert_eq!(manual.tick(secs(start, 300)), LockStep::WaitingConfirm);
+        assert!(manual.state(secs(start, 300), Vec::new(), None).waiting_confirm);
+    }
+
+    #[test]
+    fn input_is_held_only_during_strict_countdown() {
+        let start = Instant::now();
+        let strict = session(true, false, start);
+        assert!(strict.holds_input(secs(start, 299)));
+        assert!(!strict.holds_input(secs(start, 300)));
+        assert!(!session(false, false, start).holds_input(secs(start, 0)));
+    }
+
+    fn challenge_session() -> LockSession {
+        LockSession::start(request(&[], true), true, EmergencyUnlockConfig::challenge(), Instant::now())
+    }
+
+    #[test]
+    fn emergency_unlock_requires_a_method() {
+        let lock = session(true, true, Instant::now());
+        assert!(matches!(lock.emergency_attempt(), Err(LockRefusal::EmergencyUnavailable)));
+    }
+
+    #[test]
+    fn emergency_challenge_accepts_the_current_sentence() {
+        let mut lock = challenge_session();
+        let sentence = lock.emergency_challenge().unwrap();
+        assert_eq!(lock.emergency_challenge().unwrap(), sentence);
+        let attempt = lock.emergency_attempt().unwrap();
+        assert!(attempt.verify(&format!("  {}  ", sentence)));
+        assert!(!attempt.verify("apple river"));
+        assert_eq!(lock.finish_emergency_attempt(&attempt, true), Ok(EmergencyMethod::Challenge));
+    }
+
+    #[test]
+    fn failed_emergency_attempt_rotates_the_challenge() {
+        let mut lock = challenge_session();
+        let first = lock.emergency_challenge().unwrap();
+        let attempt = lock.emergency_attempt().unwrap();
+        assert_eq!(lock.finish_emergency_attempt(&attempt, false), Err(LockRefusal::EmergencyRejected));
+        assert!(lock.challenge.is_none());
+        let second = lock.emergency_challenge().unwrap();
+        let attempt = lock.emergency_attempt().unwrap();
+        assert!(attempt.verify(&second));
+        assert_eq!(attempt.verify(&first), first == second);
+    }
+
+    #[test]
+    fn answer_to_a_replaced_challenge_is_rejected() {
+        let mut lock = challenge_session();
+        lock.emergency_challenge().unwrap();
+        let stale = lock.emergency_attempt().unwrap();
+        let concurrent = lock.emergency_attempt().unwrap();
+        // 另一次尝试先答错，句子已经换掉
+        assert_eq!(lock.finish_emergency_attempt(&concurrent, false), Err(LockRefusal::EmergencyRejected));
+        lock.emergency_challenge().unwrap();
+        assert_eq!(lock.finish_emergency_attempt(&stale, true), Err(LockRefusal::EmergencyRejected));
+        assert_eq!(lock.state(Instant::now(), Vec::new(), None).emergency_attempts_left, MAX_EMERGENCY_ATTEMPTS - 1);
+    }
+
+    #[test]
+    fn emergency_unlock_locks_out_after_max_attempts() {
+        let mut lock = challenge_session();
+        for _ in 0..MAX_EMERGENCY_ATTEMPTS {
+            lock.emergency_challenge().unwrap();
+            let attempt = lock.emergency_attempt().unwrap();
+            assert_eq!(lock.finish_emergency_attempt(&attempt, false), Err(LockRefusal::EmergencyRejected));
+        }
+        assert_eq!(lock.emergency_challenge(), Err(LockRefusal::TooManyAttempts));
+        assert!(matches!(lock.emergency_attempt(), Err(LockRefusal::TooManyAttempts)));
+        assert_eq!(lock.state(Instant::now(), Vec::new(), None).emergency_attempts_left, 0);
+    }
+}
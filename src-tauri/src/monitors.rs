@@ -0,0 +1,178 @@
+// ============= 锁屏显示器跟踪 =============
+//
+// 锁屏时主窗口覆盖所在的显示器，其余每个显示器各有一个从属锁屏窗口。
+// 跟踪器记录显示器与从属窗口的对应关系，显示器布局变化时对比前后两次的
+// 显示器列表，得出需要创建、移动或关闭的窗口。
+// 显示器优先按名称识别，缩放或调整排列后位置变化的显示器仍对应原来的窗口；
+// 没有名称时按左上角位置识别。
+
+/// 显示器的名称和物理像素布局
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MonitorLayout {
+    pub name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl MonitorLayout {
+    /// 是否为同一台显示器
+    fn same_display(&self, other: &MonitorLayout) -> bool {
+        match (&self.name, &other.name) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.x == other.x && self.y == other.y,
+        }
+    }
+}
+
+/// 需要对从属锁屏窗口执行的操作
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SlaveChange {
+    /// 新接入的显示器，创建窗口
+    Create { label: String, monitor: MonitorLayout },
+    /// 显示器位置或大小变化，移动窗口
+    Move { label: String, monitor: MonitorLayout },
+    /// 显示器已断开或改为显示主窗口，关闭窗口
+    Close { label: String },
+}
+
+#[derive(Default)]
+pub struct MonitorTracker {
+    slaves: Vec<(MonitorLayout, String)>,
+    next_index: usize,  // 窗口标签只增不减，避免与正在关闭的窗口重名
+}
+
+impl MonitorTracker {
+    /// 按当前的显示器列表更新对应关系，main 为主窗口所在的显示器
+    pub fn sync(&mut self, monitors: &[MonitorLayout], main: Option<&MonitorLayout>) -> Vec<SlaveChange> {
+        let mut previous = std::mem::take(&mut self.slaves);
+        let mut changes = Vec::new();
+
+        for monitor in monitors {
+            if main.is_some_and(|main| main.same_display(monitor)) {
+                continue;
+            }
+            match previous.iter().position(|(tracked, _)| tracked.same_display(monitor)) {
+                Some(index) => {
+                    let (tracked, label) = previous.swap_remove(index);
+                    if tracked != *monitor {
+                        changes.push(SlaveChange::Move { label: label.clone(), monitor: monitor.clone() });
+                    }
+                    self.slaves.push((monitor.clone(), label));
+                }
+                None => {
+                    let label = format!("lock-slave-{}", self.next_index);
+                    self.next_index += 1;
+                    changes.push(SlaveChange::Create { label: label.clone(), monitor: monitor.clone() });
+                    self.slaves.push((monitor.clone(), label));
+                }
+            }
+        }
+
+        changes.extend(previous.into_iter().map(|(_, label)| SlaveChange::Close { label }));
+        changes
+    }
+
+    /// 窗口创建失败或被销毁时移除，下次同步时重新创建
+    pub fn forget(&mut self, label: &str) {
+        self.slaves.retain(|(_, tracked)| tracked != label);
+    }
+
+    pub fn contains(&self, label: &str) -> bool {
+        self.slaves.iter().any(|(_, tracked)| tracked == label)
+    }
+
+    pub fn labels(&self) -> Vec<String> {
+        self.slaves.iter().map(|(_, label)| label.clone()).collect()
+    }
+
+    /// 结束锁屏时取出所有窗口标签
+    pub fn take_labels(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.slaves).into_iter().map(|(_, label)| label).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: Option<&str>, x: i32, width: u32) -> MonitorLayout {
+        MonitorLayout { name: name.map(str::to_string), x, y: 0, width, height: 1080 }
+    }
+
+    fn create(label: &str, monitor: &MonitorLayout) -> SlaveChange {
+        SlaveChange::Create { label: label.to_string(), monitor: monitor.clone() }
+    }
+
+    #[test]
+    fn creates_a_window_for_each_secondary_monitor() {
+        let main = monitor(Some("eDP-1"), 0, 1920);
+        let left = monitor(Some("HDMI-1"), 1920, 1920);
+        let right = monitor(Some("DP-1"), 3840, 2560);
+        let mut tracker = MonitorTracker::default();
+
+        let changes = tracker.sync(&[main.clone(), left.clone(), right.clone()], Some(&main));
+        assert_eq!(changes, [create("lock-slave-0", &left), create("lock-slave-1", &right)]);
+        assert_eq!(tracker.labels(), ["lock-slave-0", "lock-slave-1"]);
+        // 布局没有变化时不做任何事
+        assert!(tracker.sync(&[main.clone(), left, right], Some(&main)).is_empty());
+    }
+
+    #[test]
+    fn moves_a_window_when_its_monitor_is_rearranged() {
+        let main = monitor(Some("eDP-1"), 0, 1920);
+        let mut tracker = MonitorTracker::default();
+        tracker.sync(&[main.clone(), monitor(Some("HDMI-1"), 1920, 1920)], Some(&main));
+
+        // 调整排列或缩放后按名称仍对应原来的窗口
+        let moved = monitor(Some("HDMI-1"), -2560, 2560);
+        let changes = tracker.sync(&[main.clone(), moved.clone()], Some(&main));
+        assert_eq!(changes, [SlaveChange::Move { label: "lock-slave-0".to_string(), monitor: moved }]);
+    }
+
+    #[test]
+    fn closes_the_window_of_a_removed_monitor() {
+        let main = monitor(Some("eDP-1"), 0, 1920);
+        let mut tracker = MonitorTracker::default();
+        tracker.sync(&[main.clone(), monitor(Some("HDMI-1"), 1920, 1920)], Some(&main));
+
+        let changes = tracker.sync(std::slice::from_ref(&main), Some(&main));
+        assert_eq!(changes, [SlaveChange::Close { label: "lock-slave-0".to_string() }]);
+        assert!(!tracker.contains("lock-slave-0"));
+        // 重新接入时使用新的标签，避免与正在关闭的窗口重名
+        let again = monitor(Some("HDMI-1"), 1920, 1920);
+        assert_eq!(tracker.sync(&[main.clone(), again.clone()], Some(&main)), [create("lock-slave-1", &again)]);
+    }
+
+    #[test]
+    fn unnamed_monitors_are_matched_by_position() {
+        let main = monitor(None, 0, 1920);
+        let mut tracker = MonitorTracker::default();
+        tracker.sync(&[main.clone(), monitor(None, 1920, 1920)], Some(&main));
+
+        // 左上角不变时视为同一台显示器，只调整大小
+        let resized = monitor(None, 1920, 1280);
+        let changes = tracker.sync(&[main.clone(), resized.clone()], Some(&main));
+        assert_eq!(changes, [SlaveChange::Move { label: "lock-slave-0".to_string(), monitor: resized }]);
+
+        // 左上角变化后无法识别，关闭旧窗口并创建新窗口
+        let shifted = monitor(None, 2000, 1280);
+        let changes = tracker.sync(&[main.clone(), shifted.clone()], Some(&main));
+        assert_eq!(changes, [create("lock-slave-1", &shifted), SlaveChange::Close { label: "lock-slave-0".to_string() }]);
+    }
+
+    #[test]
+    fn main_window_moving_to_another_monitor_swaps_slaves() {
+        let laptop = monitor(Some("eDP-1"), 0, 1920);
+        let external = monitor(Some("HDMI-1"), 1920, 1920);
+        let monitors = [laptop.clone(), external.clone()];
+        let mut tracker = MonitorTracker::default();
+        tracker.sync(&monitors, Some(&laptop));
+
+        let changes = tracker.sync(&monitors, Some(&external));
+        assert_eq!(changes, [create("lock-slave-1", &laptop), SlaveChange::Close { label: "lock-slave-0".to_string() }]);
+        assert_eq!(tracker.take_labels(), ["lock-slave-1"]);
+        assert!(tracker.labels().is_empty());
+    }
+}
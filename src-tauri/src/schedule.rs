@@ -0,0 +1,441 @@
+use chrono::{DateTime, Datelike, Days, FixedOffset, Local, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// ============= 日程时间工具 =============
+//
+// 固定时刻、星期掩码与时区换算。所有计算都以传入的 UTC 时间为准，
+// 不读取系统时钟。
+
+/// 一天中的时刻，JSON 中写作 "HH:MM"
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeOfDay {
+    pub hour: u32,
+    pub minute: u32,
+}
+
+impl TimeOfDay {
+    pub fn parse(s: &str) -> Option<Self> {
+        let (h, m) = s.trim().split_once(':')?;
+        let hour: u32 = h.parse().ok()?;
+        let minute: u32 = m.parse().ok()?;
+        // 允许 "24:00" 表示一天结束
+        if minute >= 60 || hour > 24 || (hour == 24 && minute != 0) {
+            return None;
+        }
+        Some(Self { hour, minute })
+    }
+
+    /// 距离零点的秒数，"24:00" 为 86400
+    pub fn seconds(self) -> u32 {
+        (self.hour * 60 + self.minute) * 60
+    }
+
+    fn to_naive(self) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(self.hour, self.minute, 0)
+    }
+}
+
+impl Serialize for TimeOfDay {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:02}:{:02}", self.hour, self.minute))
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeOfDay {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        TimeOfDay::parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid time of day: {}", s)))
+    }
+}
+
+/// 星期掩码，bit0 = 周一 ... bit6 = 周日
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WeekdayMask(pub u8);
+
+impl WeekdayMask {
+    pub const ALL: WeekdayMask = WeekdayMask(0b111_1111);
+
+    pub fn contains(self, day: Weekday) -> bool {
+        self.0 & (1 << day.num_days_from_monday()) != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 & Self::ALL.0 == 0
+    }
+}
+
+impl Default for WeekdayMask {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// 时区：缺省或 "local" 使用系统时区，"UTC"、"+08:00" 等使用固定偏移
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum TimeZoneSpec {
+    #[default]
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl TimeZoneSpec {
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if s.is_empty() || s.eq_ignore_ascii_case("local") {
+            return Some(Self::Local);
+        }
+        if s.eq_ignore_ascii_case("utc") || s == "Z" {
+            return FixedOffset::east_opt(0).map(Self::Fixed);
+        }
+        s.parse::<FixedOffset>().ok().map(Self::Fixed)
+    }
+
+    /// UTC 时间转换为该时区的本地日期时间
+    pub fn local_time(self, at: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Self::Local => at.with_timezone(&Local).naive_local(),
+            Self::Fixed(offset) => at.with_timezone(&offset).naive_local(),
+        }
+    }
+
+    /// 本地日期时间转回 UTC；夏令时跳过的时刻返回 None，重复的时刻取较早的一个
+    pub fn to_utc(self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Local => Local.from_local_datetime(&local).earliest().map(|t| t.with_timezone(&Utc)),
+            Self::Fixed(offset) => offset.from_local_datetime(&local).earliest().map(|t| t.with_timezone(&Utc)),
+        }
+    }
+}
+
+impl Serialize for TimeZoneSpec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Local => serializer.serialize_str("local"),
+            Self::Fixed(offset) => serializer.serialize_str(&offset.to_string()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeZoneSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = Option::<String>::deserialize(deserializer)?.unwrap_or_default();
+        TimeZoneSpec::parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid timezone: {}", s)))
+    }
+}
+
+/// 固定时刻日程，例如工作日的 10:30 和 15:30
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FixedTimes {
+    pub times: Vec<TimeOfDay>,
+    #[serde(default)]
+    pub weekdays: WeekdayMask,
+    #[serde(default)]
+    pub timezone: TimeZoneSpec,
+}
+
+impl FixedTimes {
+    /// 严格晚于 after 的下一次触发时间；没有可用时刻时返回 None
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.times.is_empty() || self.weekdays.is_empty() {
+            return None;
+        }
+
+        let mut times: Vec<NaiveTime> = self.times.iter().filter_map(|t| t.to_naive()).collect();
+        times.sort();
+
+        let today = self.timezone.local_time(after).date();
+        // 最多向后看 8 天，保证覆盖一整周加上今天剩余的时刻
+        for offset in 0..=7 {
+            let date = today.checked_add_days(Days::new(offset))?;
+            if !self.weekdays.contains(date.weekday()) {
+                continue;
+            }
+            for time in &times {
+                if let Some(at) = self.timezone.to_utc(date.and_time(*time)) {
+                    if at > after {
+                        return Some(at);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// 每周重复的时间段 [start, end)，end 不晚于 start 时跨越午夜；
+/// weekdays 指时间段开始的那一天
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WeeklyWindow {
+    #[serde(default)]
+    pub weekdays: WeekdayMask,
+    pub start: TimeOfDay,
+    pub end: TimeOfDay,
+}
+
+impl WeeklyWindow {
+    fn contains(&self, local: NaiveDateTime) -> bool {
+        let secs = local.num_seconds_from_midnight();
+        let (start, end) = (self.start.seconds(), self.end.seconds());
+        let today = self.weekdays.contains(local.weekday());
+        if start < end {
+            return today && secs >= start && secs < end;
+        }
+        // 跨午夜：今天开始的部分，或前一天开始、延续到今天的部分
+        let yesterday = self.weekdays.contains(local.weekday().pred());
+        (today && secs >= start) || (yesterday && secs < end)
+    }
+}
+
+/// 任务的活动时段，例如周一至周五 09:00–18:00，排除 12:00–13:00
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActiveHours {
+    pub windows: Vec<WeeklyWindow>,
+    #[serde(default)]
+    pub exclude: Vec<WeeklyWindow>,
+    #[serde(default)]
+    pub timezone: TimeZoneSpec,
+}
+
+impl ActiveHours {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let local = self.timezone.local_time(at);
+        self.windows.iter().any(|w| w.contains(local)) && !self.exclude.iter().any(|w| w.contains(local))
+    }
+
+    /// 晚于 after 的下一次进入活动时段的时间；一周内都不会进入时返回 None
+    pub fn next_start(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        // 只有时间段开始或排除时段结束时才可能进入活动状态
+        let boundaries: Vec<NaiveTime> = self.windows.iter().map(|w| w.start)
+            .chain(self.exclude.iter().map(|w| w.end))
+            .filter_map(|t| t.to_naive().or_else(|| NaiveTime::from_hms_opt(0, 0, 0)))
+            .collect();
+
+        let today = self.timezone.local_time(after).date();
+        let mut candidates: Vec<DateTime<Utc>> = (0..=8)
+            .filter_map(|offset| today.checked_add_days(Days::new(offset)))
+            .flat_map(|date| boundaries.iter().map(move |time| date.and_time(*time)))
+            .filter_map(|local| self.timezone.to_utc(local))
+            .filter(|at| *at > after)
+            .collect();
+        candidates.sort();
+        candidates.into_iter().find(|at| self.contains(*at))
+    }
+}
+
+/// 全局免打扰时段，例如 19:00–09:00 和 12:00–13:00
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub windows: Vec<WeeklyWindow>,
+    #[serde(default)]
+    pub timezone: TimeZoneSpec,
+}
+
+impl QuietHours {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let local = self.timezone.local_time(at);
+        self.windows.iter().any(|w| w.contains(local))
+    }
+
+    /// 晚于 after 的下一次离开免打扰时段的时间；一周内都不会离开时返回 None
+    pub fn next_end(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        // 只有时间段结束时才可能离开免打扰状态
+        let boundaries: Vec<NaiveTime> = self.windows.iter().map(|w| w.end)
+            .filter_map(|t| t.to_naive().or_else(|| NaiveTime::from_hms_opt(0, 0, 0)))
+            .collect();
+
+        let today = self.timezone.local_time(after).date();
+        let mut candidates: Vec<DateTime<Utc>> = (0..=8)
+            .filter_map(|offset| today.checked_add_days(Days::new(offset)))
+            .flat_map(|date| boundaries.iter().map(move |time| date.and_time(*time)))
+            .filter_map(|local| self.timezone.to_utc(local))
+            .filter(|at| *at > after)
+            .collect();
+        candidates.sort();
+        candidates.into_iter().find(|at| !self.contains(*at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 东八区 2026-10-<day> 的本地时间，10 月 12 日为周一
+    fn local(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        FixedOffset::east_opt(8 * 3600).unwrap()
+            .with_ymd_and_hms(2026, 10, day, hour, minute, 0).unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn fixed(times: &[&str], weekdays: u8, timezone: &str) -> FixedTimes {
+        FixedTimes {
+            times: times.iter().map(|t| TimeOfDay::parse(t).unwrap()).collect(),
+            weekdays: WeekdayMask(weekdays),
+            timezone: TimeZoneSpec::parse(timezone).unwrap(),
+        }
+    }
+
+    #[test]
+    fn time_of_day_parses_hours_and_minutes() {
+        assert_eq!(TimeOfDay::parse(" 9:05 "), Some(TimeOfDay { hour: 9, minute: 5 }));
+        assert_eq!(TimeOfDay::parse("24:00").map(TimeOfDay::seconds), Some(86400));
+        assert_eq!(TimeOfDay::parse("24:01"), None);
+        assert_eq!(TimeOfDay::parse("12:60"), None);
+        assert_eq!(TimeOfDay::parse("1230"), None);
+    }
+
+    #[test]
+    fn timezone_spec_parses_local_utc_and_offsets() {
+        assert_eq!(TimeZoneSpec::parse(""), Some(TimeZoneSpec::Local));
+        assert_eq!(TimeZoneSpec::parse("Local"), Some(TimeZoneSpec::Local));
+        assert_eq!(TimeZoneSpec::parse("UTC"), Some(TimeZoneSpec::Fixed(FixedOffset::east_opt(0).unwrap())));
+        assert_eq!(TimeZoneSpec::parse("+08:00"), Some(TimeZoneSpec::Fixed(FixedOffset::east_opt(8 * 3600).unwrap())));
+        assert_eq!(TimeZoneSpec::parse("Asia/Shanghai"), None);
+    }
+
+    #[test]
+    fn fixed_times_pick_the_next_time_today_or_tomorrow() {
+        let schedule = fixed(&["15:30", "10:30"], 0b111_1111, "+08:00");
+        assert_eq!(schedule.next_after(local(14, 9, 0)), Some(local(14, 10, 30)));
+        // 严格晚于给定时间
+        assert_eq!(schedule.next_after(local(14, 10, 30)), Some(local(14, 15, 30)));
+        assert_eq!(schedule.next_after(local(14, 16, 0)), Some(local(15, 10, 30)));
+    }
+
+    #[test]
+    fn fixed_times_skip_days_outside_the_weekday_mask() {
+        // 周一至周五
+        let workdays = fixed(&["10:30"], 0b001_1111, "+08:00");
+        assert_eq!(workdays.next_after(local(16, 11, 0)), Some(local(19, 10, 30)));
+        // 只有周三，从周三之后要等一整周
+        let wednesday = fixed(&["10:30"], 0b000_0100, "+08:00");
+        assert_eq!(wednesday.next_after(local(14, 11, 0)), Some(local(21, 10, 30)));
+        assert_eq!(wednesday.next_after(local(14, 10, 0)), Some(local(14, 10, 30)));
+    }
+
+    #[test]
+    fn fixed_times_without_times_or_days_never_trigger() {
+        assert_eq!(fixed(&[], 0b111_1111, "+08:00").next_after(local(14, 9, 0)), None);
+        assert_eq!(fixed(&["10:30"], 0, "+08:00").next_after(local(14, 9, 0)), None);
+        // 只有第 8 位的掩码不含任何一天
+        assert_eq!(fixed(&["10:30"], 0b1000_0000, "+08:00").next_after(local(14, 9, 0)), None);
+    }
+
+    #[test]
+    fn fixed_times_use_the_weekday_of_their_own_timezone() {
+        // 东八区周六 01:00 时 UTC 仍是周五 17:00
+        let utc_friday = fixed(&["18:00"], 0b001_0000, "UTC");
+        assert_eq!(utc_friday.next_after(local(17, 1, 0)), Some(local(17, 2, 0)));
+        let utc_nine = fixed(&["09:00"], 0b111_1111, "UTC");
+        assert_eq!(utc_nine.next_after(local(14, 16, 0)), Some(local(14, 17, 0)));
+    }
+
+    /// 在设置了 TZ 的子进程中重新运行指定测试，避免修改并行测试共用的环境变量；
+    /// 已经在子进程中时返回 false，由调用者继续执行测试内容
+    fn rerun_with_tz(test: &str, tz: &str) -> bool {
+        if std::env::var_os("SCHEDULE_TEST_TZ").is_some() {
+            return false;
+        }
+        let status = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", test, "--test-threads=1"])
+            .env("SCHEDULE_TEST_TZ", "1")
+            .env("TZ", tz)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        true
+    }
+
+    /// 美国东部时间，2026-03-08 02:00 跳到 03:00
+    const NEW_YORK: &str = "EST5EDT,M3.2.0,M11.1.0";
+
+    fn utc(month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, month, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn fixed_time_in_dst_gap_is_skipped() {
+        if rerun_with_tz("schedule::tests::fixed_time_in_dst_gap_is_skipped", NEW_YORK) {
+            return;
+        }
+        let schedule = fixed(&["02:30"], 0b111_1111, "local");
+        // 3 月 7 日 12:00 EST 之后的 3 月 8 日 02:30 不存在，改为 3 月 9 日 02:30 EDT
+        assert_eq!(schedule.next_after(utc(3, 7, 17, 0)), Some(utc(3, 9, 6, 30)));
+        assert_eq!(schedule.next_after(utc(3, 6, 17, 0)), Some(utc(3, 7, 7, 30)));
+    }
+
+    fn window(weekdays: u8, start: &str, end: &str) -> WeeklyWindow {
+        WeeklyWindow {
+            weekdays: WeekdayMask(weekdays),
+            start: TimeOfDay::parse(start).unwrap(),
+            end: TimeOfDay::parse(end).unwrap(),
+        }
+    }
+
+    /// 周一至周五 09:00–18:00，排除 12:00–13:00
+    fn office_hours() -> ActiveHours {
+        ActiveHours {
+            windows: vec![window(0b001_1111, "09:00", "18:00")],
+            exclude: vec![window(0b111_1111, "12:00", "13:00")],
+            timezone: TimeZoneSpec::parse("+08:00").unwrap(),
+        }
+    }
+
+    #[test]
+    fn active_hours_contain_the_window_minus_exclusions() {
+        let hours = office_hours();
+        assert!(!hours.contains(local(14, 8, 59)));
+        assert!(hours.contains(local(14, 9, 0)));
+        assert!(!hours.contains(local(14, 12, 0)));
+        assert!(hours.contains(local(14, 13, 0)));
+        // 结束时刻不包含在内
+        assert!(!hours.contains(local(14, 18, 0)));
+        assert!(!hours.contains(local(17, 10, 0)));
+    }
+
+    #[test]
+    fn cross_midnight_window_belongs_to_its_start_day() {
+        // 只有周五晚上 22:00 到周六 06:00
+        let hours = ActiveHours {
+            windows: vec![window(0b001_0000, "22:00", "06:00")],
+            exclude: Vec::new(),
+            timezone: TimeZoneSpec::parse("+08:00").unwrap(),
+        };
+        assert!(!hours.contains(local(16, 5, 0)));
+        assert!(hours.contains(local(16, 23, 0)));
+        assert!(hours.contains(local(17, 5, 59)));
+        assert!(!hours.contains(local(17, 6, 0)));
+        assert!(!hours.contains(local(17, 23, 0)));
+        assert_eq!(hours.next_start(local(17, 6, 0)), Some(local(23, 22, 0)));
+    }
+
+    #[test]
+    fn window_ending_at_midnight_covers_the_last_minute() {
+        let hours = ActiveHours {
+            windows: vec![window(0b111_1111, "20:00", "24:00")],
+            exclude: Vec::new(),
+            timezone: TimeZoneSpec::parse("+08:00").unwrap(),
+        };
+        assert!(hours.contains(local(14, 23, 59)));
+        assert!(!hours.contains(local(15, 0, 0)));
+    }
+
+    #[test]
+    fn next_start_skips_weekends_and_exclusions() {
+        let hours = office_hours();
+        assert_eq!(hours.next_start(local(14, 7, 0)), Some(local(14, 9, 0)));
+        // 排除时段结束时重新进入活动时段
+        assert_eq!(hours.next_start(local(14, 12, 10)), Some(local(14, 13, 0)));
+        assert_eq!(hours.next_start(local(16, 18, 0)), Some(local(19, 9, 0)));
+    }
+
+    #[test]
+    fn next_start_is_none_when_never_active() {
+        let hours = ActiveHours {
+            windows: vec![window(0b001_1111, "12:00", "13:00")],
+            exclude: vec![window(0b111_1111, "11:00", "14:00")],
+            timezone: TimeZoneSpec::parse("+08:00").unwrap(),
+        };
+        assert_eq!(hours.next_start(local(14, 7, 0)), None);
+    }
+}
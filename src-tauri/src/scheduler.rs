@@ -0,0 +1,2259 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+
+use crate::app_rules::{ActiveWindow, AppRule, AppRules};
+use crate::schedule::{ActiveHours, FixedTimes, QuietHours};
+
+// ============= 调度引擎 =============
+//
+// 所有计时逻辑都集中在 Scheduler 中，时间点由调用方传入，
+// 不直接读取系统时钟，也不依赖全局状态。
+// Tauri 侧的定时器线程只负责按秒驱动 tick() 并转发事件。
+
+/// 一个时间点：单调时钟用于间隔计时，墙上时间用于固定时刻日程
+#[derive(Clone, Copy, Debug)]
+pub struct Now {
+    pub instant: Instant,
+    pub wall: DateTime<Utc>,
+}
+
+/// 时钟抽象，便于注入可控的时间源
+pub trait Clock {
+    fn now(&self) -> Now;
+}
+
+/// 使用系统时钟
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Now {
+        Now {
+            instant: Instant::now(),
+            wall: Utc::now(),
+        }
+    }
+}
+
+/// 空闲时间来源（秒）
+pub trait IdleSource {
+    /// 后端名称，用于向前端报告
+    fn name(&self) -> &'static str;
+    /// 空闲秒数；后端不可用时返回 None
+    fn idle_seconds(&mut self) -> Option<u64>;
+}
+
+/// 屏保抑制状态来源，例如视频播放器持有的 Inhibit
+pub trait InhibitSource {
+    fn is_inhibited(&mut self) -> bool;
+}
+
+/// 前台的活动窗口，用于全屏检测和应用规则
+pub trait WindowSource {
+    fn active_window(&mut self) -> Option<ActiveWindow>;
+}
+
+/// 是否正在通话（摄像头或麦克风被占用）
+pub trait CallSource {
+    fn in_call(&mut self) -> bool;
+}
+
+/// 屏保被抑制（如播放视频）期间任务如何计时
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InhibitPolicy {
+    /// 被动时间：倒计时冻结，既不计入工作也不算休息
+    #[default]
+    Passive,
+    /// 有输入时照常计时，没有输入时冻结；只有真实的无输入时长计入休息
+    Idle,
+    /// 视为工作，即使没有输入也照常计时和提醒
+    Active,
+}
+
+/// 前台有全屏程序时到期的提醒如何处理
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FullscreenPolicy {
+    /// 照常提醒
+    Ignore,
+    /// 推迟到退出全屏，最长推迟 max_defer_seconds
+    #[default]
+    Defer,
+    /// 只发通知，不弹出锁屏
+    NotifyOnly,
+}
+
+/// 提醒被推迟的原因
+#[derive(Clone, Copy, serde::Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeferReason {
+    Fullscreen,
+    App,
+    Call,
+}
+
+/// 免打扰时段内到期的提醒如何处理
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuietPolicy {
+    /// 丢弃，直接等待下一次
+    Drop,
+    /// 免打扰结束时逐个提醒
+    #[default]
+    Defer,
+    /// 免打扰结束时合并为一次提醒
+    Collapse,
+}
+
+/// 间隔任务的计时方式
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CountMode {
+    /// 按墙上时间计时，空闲只按阈值整体判断
+    #[default]
+    WallClock,
+    /// 只统计有输入的时间，短暂离开也会暂停计时
+    ActiveTime,
+}
+
+/// 提醒一直未被处理时的升级动作
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EscalationAction {
+    /// 再次发送系统通知
+    Renotify,
+    /// 播放提示音
+    Sound,
+    /// 强制进入锁屏
+    LockScreen,
+}
+
+/// 触发后经过 after_seconds 秒仍未处理时执行 action
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+pub struct EscalationStep {
+    pub after_seconds: u64,
+    pub action: EscalationAction,
+}
+
+/// 已执行的升级步骤
+#[derive(Clone, serde::Serialize, Debug)]
+pub struct EscalationRecord {
+    pub action: EscalationAction,
+    pub after_seconds: u64,
+    pub at: i64,  // 执行时间（Unix 毫秒）
+}
+
+/// 任务的推迟规则
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+#[serde(default)]
+pub struct SnoozePolicy {
+    pub max_count: u32,  // 每轮最多推迟次数
+    pub durations: Vec<u64>,  // 允许的推迟时长（分钟）
+    pub cooldown_seconds: u64,  // 距上次推迟至少间隔的秒数
+    pub allow_in_strict: bool,  // 严格模式下是否允许推迟
+}
+
+impl Default for SnoozePolicy {
+    fn default() -> Self {
+        Self {
+            max_count: 1,
+            durations: vec![5],
+            cooldown_seconds: 0,
+            allow_in_strict: false,
+        }
+    }
+}
+
+/// 推迟被拒绝的原因
+#[derive(Clone, Copy, serde::Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnoozeRefusal {
+    UnknownTask,
+    LimitReached,
+    DurationNotAllowed,
+    CoolingDown,
+    StrictMode,
+}
+
+impl std::fmt::Display for SnoozeRefusal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::UnknownTask => "unknown task",
+            Self::LimitReached => "snooze limit reached",
+            Self::DurationNotAllowed => "snooze duration not allowed",
+            Self::CoolingDown => "snooze is cooling down",
+            Self::StrictMode => "snooze is disabled in strict mode",
+        };
+        f.write_str(text)
+    }
+}
+
+/// 任务的触发方式
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug, Default, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TaskSchedule {
+    /// 从上次重置起每隔 interval 分钟触发
+    #[default]
+    Interval,
+    /// 在固定的时刻触发
+    Fixed(FixedTimes),
+    /// 番茄钟：工作 interval 分钟后休息，每完成 long_break_every 个进行一次长休息
+    Pomodoro(PomodoroConfig),
+}
+
+/// 番茄钟的休息设置
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+pub struct PomodoroConfig {
+    pub short_break: u64,  // 短休息分钟数
+    pub long_break: u64,  // 长休息分钟数
+    pub long_break_every: u32,  // 每完成几个番茄钟进行一次长休息
+}
+
+/// 番茄钟当前所处的阶段
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PomodoroPhase {
+    #[default]
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+/// 番茄钟的进度，随计时进度一起保存
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug, Default, PartialEq)]
+pub struct PomodoroState {
+    pub phase: PomodoroPhase,
+    pub done_in_set: u32,  // 本组已完成的番茄钟数，长休息后清零
+    pub completed: u32,  // completed_on 当天完成的番茄钟数
+    pub completed_on: Option<NaiveDate>,  // 本地日期
+}
+
+impl PomodoroState {
+    fn completed_today(&self, today: NaiveDate) -> u32 {
+        if self.completed_on == Some(today) { self.completed } else { 0 }
+    }
+}
+
+/// 倒计时中的番茄钟状态
+#[derive(Clone, serde::Serialize)]
+pub struct PomodoroInfo {
+    pub phase: PomodoroPhase,
+    pub cycle: u32,  // 本组中的第几个番茄钟，从 1 开始
+    pub cycles: u32,  // 每组的番茄钟数
+    pub completed: u32,  // 今天完成的番茄钟数
+    pub break_seconds: u64,  // 当前或下一次休息的时长
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug)]
+pub struct TaskConfig {
+    pub id: String,
+    pub title: String,
+    pub desc: String,
+    pub interval: u64,  // 分钟
+    pub enabled: bool,
+    pub icon: String,
+    #[serde(default)]
+    pub auto_reset_on_idle: bool,  // 空闲时自动重置
+    #[serde(default)]
+    pub schedule: TaskSchedule,
+    #[serde(default)]
+    pub active_hours: Option<ActiveHours>,  // 活动时段，缺省为全天
+    #[serde(default)]
+    pub inhibit_policy: InhibitPolicy,  // 屏保被抑制时的计时方式
+    #[serde(default)]
+    pub count_mode: CountMode,
+    #[serde(default)]
+    pub idle_threshold: Option<u64>,  // 空闲超过该秒数视为已休息，缺省使用全局阈值
+    #[serde(default)]
+    pub escalation: Vec<EscalationStep>,  // 提醒未处理时的升级步骤，按 after_seconds 执行
+    #[serde(default)]
+    pub snooze: SnoozePolicy,
+    #[serde(default)]
+    pub satisfies: Vec<String>,  // 完成本任务的休息时一并视为完成休息的任务
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct CountdownInfo {
+    pub id: String,
+    pub remaining: u64,  // 剩余秒数
+    pub total: u64,      // 总秒数
+    pub enabled: bool,
+    pub snoozed: bool,   // 是否推迟中
+    pub snooze_remaining: u64, // 推迟剩余时间
+    pub snooze_count: u32, // 当前已推迟次数
+    pub snooze_options: Vec<u64>, // 允许的推迟时长（分钟）
+    pub snooze_blocked: Option<SnoozeRefusal>, // 当前不能推迟的原因
+    pub next_due_at: Option<i64>, // 固定时刻任务的下次触发时间（Unix 毫秒）
+    pub outside_hours: bool, // 是否处于活动时段之外
+    pub resumes_at: Option<i64>, // 活动时段之外时，下次恢复计时的时间（Unix 毫秒）
+    pub passive: bool, // 屏保被抑制而冻结
+    pub count_mode: CountMode,
+    pub inactive: bool, // 按有效时间计时且当前没有输入
+    pub app_paused: bool, // 前台不是规则要求的应用而冻结
+    pub triggered_at: Option<i64>, // 提醒触发后尚未处理时，触发时间（Unix 毫秒）
+    pub escalations: Vec<EscalationRecord>, // 本次提醒已执行的升级步骤
+    pub pause_remaining: Option<u64>, // 定时暂停时，距自动恢复的秒数
+    pub quiet_until: Option<i64>, // 处于免打扰时段时，结束时间（Unix 毫秒）
+    pub pomodoro: Option<PomodoroInfo>, // 番茄钟任务的阶段和进度
+    pub covered_by: Option<String>, // 等待该任务的提醒完成后一并重置，期间不单独提醒
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct TaskTriggeredPayload {
+    pub id: String,
+    pub title: String,
+    pub desc: String,
+    pub icon: String,
+    pub task_ids: Vec<String>,  // 本次提醒包含的全部任务，第一个为 id，其余为合并进来的任务
+    pub notify_only: bool,  // 只发通知，不进入锁屏
+    pub lock_duration: Option<u64>,  // 锁屏秒数，包含番茄钟时为其休息时长，否则由前端按任务设置
+}
+
+/// 到期的提醒被推迟，推迟开始时发送一次
+#[derive(Clone, serde::Serialize)]
+pub struct ReminderDeferredPayload {
+    pub task_ids: Vec<String>,
+    pub reason: DeferReason,
+    pub until: Option<i64>,  // 最迟在该时间提醒（Unix 毫秒），None 为一直推迟到原因消失
+    pub app: Option<String>,  // 原因为 app 时，要求推迟的前台应用
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct IdleStatus {
+    pub is_idle: bool,
+    pub idle_seconds: u64,
+    pub threshold: u64,
+    pub inhibited: bool,  // 是否有程序抑制屏保
+}
+
+/// 按有效时间计时的任务，距上次输入不超过该秒数仍视为在操作
+const ACTIVE_INPUT_GRACE_SECONDS: u64 = 5;
+
+/// 墙上时间比单调时钟多走出超过该秒数时，视为系统经历了休眠
+const SUSPEND_GAP_SECONDS: i64 = 30;
+
+/// 持久化的计时进度，以墙上时间表示，跨重启恢复
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug)]
+pub struct TimerSnapshot {
+    pub saved_at: DateTime<Utc>,
+    pub tasks: Vec<TaskSnapshot>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug)]
+pub struct TaskSnapshot {
+    pub id: String,
+    pub interval: u64,
+    pub elapsed_secs: i64,  // 本轮已计时秒数，推迟时可能为负
+    pub running: bool,  // 保存时是否正在计时（未暂停、未冻结）
+    pub snoozed: bool,
+    pub snooze_count: u32,
+    pub next_due: Option<DateTime<Utc>>,  // 固定时刻任务的下次触发时间
+    #[serde(default)]
+    pub pomodoro: Option<PomodoroState>,  // 番茄钟任务的阶段和完成数
+}
+
+/// 提醒未处理而执行的升级动作
+#[derive(Clone, serde::Serialize)]
+pub struct EscalationEvent {
+    pub id: String,
+    pub title: String,
+    pub task_ids: Vec<String>,
+    pub action: EscalationAction,
+    pub step: usize,  // 第几个升级步骤，从 0 开始
+    pub unanswered_seconds: u64,
+    pub lock_duration: Option<u64>,  // 升级为锁屏时的秒数，含义与 TaskTriggeredPayload 相同
+}
+
+/// 用户自然离开达到任务的空闲阈值，任务已自动重置
+#[derive(Clone, serde::Serialize)]
+pub struct NaturalBreakPayload {
+    pub task_ids: Vec<String>,
+    pub idle_seconds: u64,
+}
+
+/// 一次 tick 的结果
+pub struct TickOutput {
+    pub triggered: Vec<TaskTriggeredPayload>,
+    pub natural_break: Option<NaturalBreakPayload>,
+    pub escalations: Vec<EscalationEvent>,
+    pub deferred: Option<ReminderDeferredPayload>,
+    pub idle_status: Option<IdleStatus>,  // 仅在空闲状态变化时返回
+    pub countdowns: Vec<CountdownInfo>,
+}
+
+/// 已触发、等待用户处理的提醒
+#[derive(Clone, Debug)]
+struct PendingReminder {
+    since: Instant,
+    since_wall: DateTime<Utc>,
+    task_ids: Vec<String>,  // 合并在这次提醒中的任务
+    lock_duration: Option<u64>,  // 与提醒一起发出的锁屏秒数，升级为锁屏时沿用
+    fired: Vec<EscalationRecord>,
+}
+
+#[derive(Clone, Debug)]
+struct TaskTimer {
+    config: TaskConfig,
+    reset_time: Instant,
+    triggered: bool,  // 本轮是否已触发
+    pending: Option<PendingReminder>,  // 由本任务发出、尚未处理的提醒（合并进来的任务为 None）
+    frozen_at: Option<Instant>,  // 禁用、处于活动时段之外或被动计时的时间点，用于冻结倒计时
+    off_hours: bool,  // 是否处于活动时段之外
+    passive: bool,  // 屏保被抑制且策略为被动时间
+    inactive: bool,  // 按有效时间计时且当前没有输入
+    app_paused: bool,  // 前台不是「只在前台时计时」规则中的应用
+    idle: bool,  // 按该任务的策略，用户当前是否空闲
+    snoozed: bool, // 是否处于推迟状态
+    snooze_count: u32, // 当前已推迟次数
+    last_snooze: Option<Instant>,  // 上次推迟的时间点，用于冷却
+    // 固定时刻任务
+    period_start: DateTime<Utc>,  // 本轮等待开始的墙上时间
+    next_due: Option<DateTime<Utc>>,  // 下次触发时间
+    pomodoro: PomodoroState,  // 番茄钟任务的进度
+}
+
+impl TaskTimer {
+    fn new(config: TaskConfig, now: Now) -> Self {
+        let mut timer = Self {
+            config,
+            reset_time: now.instant,
+            triggered: false,
+            pending: None,
+            frozen_at: None,
+            off_hours: false,
+            passive: false,
+            inactive: false,
+            app_paused: false,
+            idle: false,
+            snoozed: false,
+            snooze_count: 0,
+            last_snooze: None,
+            period_start: now.wall,
+            next_due: None,
+            pomodoro: PomodoroState::default(),
+        };
+        timer.schedule_next(now);
+        timer.update_freeze(now, 0);
+        timer
+    }
+
+    fn is_off_hours(&self, now: Now) -> bool {
+        self.config.active_hours.as_ref().is_some_and(|hours| !hours.contains(now.wall))
+    }
+
+    /// 根据启用状态、活动时段、被动计时和输入情况冻结或恢复倒计时；
+    /// idle_for 为距上次输入的秒数，无输入冻结从宽限期结束时算起
+    fn update_freeze(&mut self, now: Now, idle_for: u64) {
+        self.off_hours = self.is_off_hours(now);
+        let should_freeze = !self.config.enabled || self.off_hours || self.passive || self.inactive || self.app_paused;
+
+        match self.frozen_at {
+            None if should_freeze => {
+                let late = if self.inactive { idle_for.saturating_sub(ACTIVE_INPUT_GRACE_SECONDS) } else { 0 };
+                let frozen_at = now.instant.checked_sub(Duration::from_secs(late)).unwrap_or(now.instant);
+                self.frozen_at = Some(frozen_at.max(self.reset_time.min(now.instant)));
+            }
+            Some(frozen_at) if !should_freeze => {
+                // 补偿冻结期间的时间
+                self.reset_time += now.instant.duration_since(frozen_at);
+                self.frozen_at = None;
+                // 固定时刻任务跳过冻结期间错过的时刻
+                if self.next_due.is_some_and(|due| due <= now.wall) {
+                    self.schedule_next(now);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn fixed_times(&self) -> Option<&FixedTimes> {
+        match &self.config.schedule {
+            TaskSchedule::Fixed(fixed) => Some(fixed),
+            TaskSchedule::Interval | TaskSchedule::Pomodoro(_) => None,
+        }
+    }
+
+    fn pomodoro_config(&self) -> Option<&PomodoroConfig> {
+        match &self.config.schedule {
+            TaskSchedule::Pomodoro(pomodoro) => Some(pomodoro),
+            _ => None,
+        }
+    }
+
+    /// 工作阶段到期，计入完成数并进入短休息或长休息
+    fn start_break(&mut self, now: Now) {
+        let Some(every) = self.pomodoro_config().map(|c| c.long_break_every.max(1)) else {
+            return;
+        };
+        let state = &mut self.pomodoro;
+        if state.phase != PomodoroPhase::Work {
+            return;
+        }
+        let today = now.wall.with_timezone(&Local).date_naive();
+        state.completed = state.completed_today(today) + 1;
+        state.completed_on = Some(today);
+        state.done_in_set += 1;
+        state.phase = if state.done_in_set >= every { PomodoroPhase::LongBreak } else { PomodoroPhase::ShortBreak };
+    }
+
+    /// 休息结束，回到工作阶段；长休息后开始新的一组
+    fn finish_break(&mut self) {
+        match self.pomodoro.phase {
+            PomodoroPhase::Work => {}
+            PomodoroPhase::ShortBreak => self.pomodoro.phase = PomodoroPhase::Work,
+            PomodoroPhase::LongBreak => {
+                self.pomodoro.phase = PomodoroPhase::Work;
+                self.pomodoro.done_in_set = 0;
+            }
+        }
+    }
+
+    /// 当前休息（工作阶段为下一次休息）的秒数，非番茄钟任务为 None
+    fn break_seconds(&self) -> Option<u64> {
+        let config = self.pomodoro_config()?;
+        let long = match self.pomodoro.phase {
+            PomodoroPhase::Work => self.pomodoro.done_in_set + 1 >= config.long_break_every.max(1),
+            PomodoroPhase::ShortBreak => false,
+            PomodoroPhase::LongBreak => true,
+        };
+        Some(if long { config.long_break } else { config.short_break } * 60)
+    }
+
+    fn pomodoro_info(&self, now: Now) -> Option<PomodoroInfo> {
+        let config = self.pomodoro_config()?;
+        let state = &self.pomodoro;
+        Some(PomodoroInfo {
+            phase: state.phase,
+            cycle: if state.phase == PomodoroPhase::Work { state.done_in_set + 1 } else { state.done_in_set },
+            cycles: config.long_break_every.max(1),
+            completed: state.completed_today(now.wall.with_timezone(&Local).date_naive()),
+            break_seconds: self.break_seconds()?,
+        })
+    }
+
+    /// 开始新一轮等待：间隔任务从现在重新计时，固定时刻任务指向下一个时刻，
+    /// 番茄钟在休息后回到工作阶段
+    fn restart(&mut self, now: Now) {
+        self.finish_break();
+        self.reset_time = now.instant;
+        self.triggered = false;
+        self.pending = None;
+        self.schedule_next(now);
+        // 如果任务处于冻结状态，也更新冻结时间点
+        if self.frozen_at.is_some() {
+            self.frozen_at = Some(now.instant);
+        }
+    }
+
+    /// 补偿暂停、锁屏等全局停顿的时间；冻结中的任务同时后移冻结时间点，避免解冻时重复补偿
+    fn compensate(&mut self, duration: Duration, now: Now) {
+        self.reset_time += duration;
+        // 停顿期间不计入提醒未处理的时长
+        if let Some(pending) = &mut self.pending {
+            pending.since = (pending.since + duration).min(now.instant);
+        }
+        if let Some(frozen_at) = self.frozen_at {
+            self.frozen_at = Some((frozen_at + duration).min(now.instant));
+        }
+    }
+
+    fn schedule_next(&mut self, now: Now) {
+        self.period_start = now.wall;
+        self.next_due = self.fixed_times().and_then(|fixed| fixed.next_after(now.wall));
+    }
+
+    fn reset(&mut self, now: Now) {
+        self.restart(now);
+        self.snoozed = false;
+        self.snooze_count = 0;
+    }
+
+    fn snooze(&mut self, minutes: u64, now: Now) {
+        let snooze_duration = Duration::from_secs(minutes * 60);
+        if self.fixed_times().is_some() {
+            self.period_start = now.wall;
+            self.next_due = Some(now.wall + chrono::Duration::seconds(snooze_duration.as_secs() as i64));
+        } else {
+            let total_duration = Duration::from_secs(self.config.interval * 60);
+
+            // reset_time = now + snooze - total
+            if snooze_duration >= total_duration {
+                self.reset_time = now.instant + (snooze_duration - total_duration);
+            } else {
+                self.reset_time = now.instant - (total_duration - snooze_duration);
+            }
+        }
+
+        self.triggered = false;
+        self.pending = None;
+        self.snoozed = true;
+        self.snooze_count += 1;
+        self.last_snooze = Some(now.instant);
+    }
+
+    /// 按任务的推迟规则检查能否推迟；minutes 为 None 时只检查次数、冷却和严格模式
+    fn check_snooze(&self, minutes: Option<u64>, strict_mode: bool, now: Now) -> Result<(), SnoozeRefusal> {
+        let policy = &self.config.snooze;
+        if strict_mode && !policy.allow_in_strict {
+            return Err(SnoozeRefusal::StrictMode);
+        }
+        if self.snooze_count >= policy.max_count {
+            return Err(SnoozeRefusal::LimitReached);
+        }
+        if minutes.is_some_and(|m| !policy.durations.contains(&m)) || policy.durations.is_empty() {
+            return Err(SnoozeRefusal::DurationNotAllowed);
+        }
+        let cooldown = Duration::from_secs(policy.cooldown_seconds);
+        if self.last_snooze.is_some_and(|last| now.instant.saturating_duration_since(last) < cooldown) {
+            return Err(SnoozeRefusal::CoolingDown);
+        }
+        Ok(())
+    }
+
+    /// 处于空闲、冻结或已触发状态的任务不检查触发
+    fn runnable(&self) -> bool {
+        !self.idle && self.frozen_at.is_none() && !self.triggered
+    }
+
+    fn is_due(&self, now: Now) -> bool {
+        if self.fixed_times().is_some() {
+            return self.next_due.is_some_and(|due| now.wall >= due);
+        }
+        let elapsed = now.instant.saturating_duration_since(self.reset_time).as_secs();
+        elapsed >= self.config.interval * 60
+    }
+
+    fn snapshot(&self, suspended_since: Option<Instant>, now: Now) -> TaskSnapshot {
+        // 冻结或暂停期间不计时，以较早的停止时间点为准
+        let stopped_at = self.frozen_at.into_iter().chain(suspended_since).min();
+        let effective_now = stopped_at.unwrap_or(now.instant);
+        let elapsed_secs = if effective_now >= self.reset_time {
+            effective_now.duration_since(self.reset_time).as_secs() as i64
+        } else {
+            -(self.reset_time.duration_since(effective_now).as_secs() as i64)
+        };
+
+        TaskSnapshot {
+            id: self.config.id.clone(),
+            interval: self.config.interval,
+            elapsed_secs,
+            running: stopped_at.is_none(),
+            snoozed: self.snoozed,
+            snooze_count: self.snooze_count,
+            next_due: self.next_due,
+            pomodoro: self.pomodoro_config().map(|_| self.pomodoro.clone()),
+        }
+    }
+
+    /// 从快照恢复进度；offline_secs 为离线期间需要计入的时长
+    fn restore(&mut self, snapshot: &TaskSnapshot, offline_secs: u64, now: Now) {
+        // 按有效时间计时的任务不计离线时间
+        let counted = if snapshot.running && self.config.count_mode == CountMode::WallClock {
+            offline_secs as i64
+        } else {
+            0
+        };
+        // 超时的任务恢复为刚好到期，下一次 tick 重新触发提醒
+        let total = (self.config.interval * 60) as i64;
+        let elapsed = (snapshot.elapsed_secs + counted).min(total);
+        self.reset_time = if elapsed >= 0 {
+            now.instant.checked_sub(Duration::from_secs(elapsed as u64)).unwrap_or(now.instant)
+        } else {
+            now.instant + Duration::from_secs(elapsed.unsigned_abs())
+        };
+        if self.fixed_times().is_some() && snapshot.next_due.is_some() {
+            self.next_due = snapshot.next_due;
+        }
+        if let Some(pomodoro) = snapshot.pomodoro.as_ref().filter(|_| self.pomodoro_config().is_some()) {
+            self.pomodoro = pomodoro.clone();
+        }
+        self.triggered = false;
+        self.pending = None;
+        self.snoozed = snapshot.snoozed;
+        self.snooze_count = snapshot.snooze_count;
+        if self.frozen_at.is_some() {
+            self.frozen_at = Some(now.instant);
+        }
+    }
+
+    fn countdown(&self, now: Now, strict_mode: bool) -> CountdownInfo {
+        let mut info = if self.fixed_times().is_some() {
+            self.fixed_countdown(now)
+        } else {
+            self.interval_countdown(now)
+        };
+        info.snooze_blocked = self.check_snooze(None, strict_mode, now).err();
+        info.pomodoro = self.pomodoro_info(now);
+        info
+    }
+
+    fn interval_countdown(&self, now: Now) -> CountdownInfo {
+
+        let total_secs = self.config.interval * 60;
+
+        // 如果任务被冻结，使用冻结时间点计算 elapsed，这样时间就"冻结"了
+        let effective_now = self.frozen_at.unwrap_or(now.instant);
+
+        let remaining = if self.reset_time > effective_now {
+            let wait_time = self.reset_time.duration_since(effective_now).as_secs();
+            total_secs + wait_time
+        } else {
+            let elapsed = effective_now.saturating_duration_since(self.reset_time).as_secs();
+            total_secs.saturating_sub(elapsed)
+        };
+
+        let snooze_remaining = self.reset_time.saturating_duration_since(now.instant).as_secs();
+
+        CountdownInfo {
+            id: self.config.id.clone(),
+            remaining,
+            total: total_secs,
+            enabled: self.config.enabled,
+            snoozed: self.snoozed,
+            snooze_remaining,
+            snooze_count: self.snooze_count,
+            snooze_options: self.config.snooze.durations.clone(),
+            snooze_blocked: None,
+            next_due_at: None,
+            outside_hours: self.off_hours,
+            resumes_at: self.resumes_at(now),
+            passive: self.passive,
+            count_mode: self.config.count_mode,
+            inactive: self.inactive,
+            app_paused: self.app_paused,
+            triggered_at: self.pending.as_ref().map(|p| p.since_wall.timestamp_millis()),
+            escalations: self.pending.as_ref().map(|p| p.fired.clone()).unwrap_or_default(),
+            pause_remaining: None,
+            quiet_until: None,
+            pomodoro: None,
+            covered_by: None,
+        }
+    }
+
+    fn fixed_countdown(&self, now: Now) -> CountdownInfo {
+        let seconds_until = |at: DateTime<Utc>| (at - now.wall).num_seconds().max(0) as u64;
+        let (remaining, total) = match self.next_due {
+            Some(due) => (seconds_until(due), (due - self.period_start).num_seconds().max(0) as u64),
+            None => (0, 0),
+        };
+
+        CountdownInfo {
+            id: self.config.id.clone(),
+            remaining,
+            total,
+            enabled: self.config.enabled,
+            snoozed: self.snoozed,
+            snooze_remaining: if self.snoozed { remaining } else { 0 },
+            snooze_count: self.snooze_count,
+            snooze_options: self.config.snooze.durations.clone(),
+            snooze_blocked: None,
+            next_due_at: self.next_due.map(|due| due.timestamp_millis()),
+            outside_hours: self.off_hours,
+            resumes_at: self.resumes_at(now),
+            passive: self.passive,
+            count_mode: self.config.count_mode,
+            inactive: false,
+            app_paused: self.app_paused,
+            triggered_at: self.pending.as_ref().map(|p| p.since_wall.timestamp_millis()),
+            escalations: self.pending.as_ref().map(|p| p.fired.clone()).unwrap_or_default(),
+            pause_remaining: None,
+            quiet_until: None,
+            pomodoro: None,
+            covered_by: None,
+        }
+    }
+
+    /// 执行到期的升级步骤
+    fn escalate(&mut self, now: Now) -> Vec<EscalationEvent> {
+        let Some(pending) = &mut self.pending else {
+            return Vec::new();
+        };
+        let unanswered_seconds = now.instant.saturating_duration_since(pending.since).as_secs();
+
+        let mut steps: Vec<&EscalationStep> = self.config.escalation.iter().collect();
+        steps.sort_by_key(|step| step.after_seconds);
+
+        let mut events = Vec::new();
+        while let Some(step) = steps.get(pending.fired.len()) {
+            if step.after_seconds > unanswered_seconds {
+                break;
+            }
+            pending.fired.push(EscalationRecord {
+                action: step.action,
+                after_seconds: step.after_seconds,
+                at: now.wall.timestamp_millis(),
+            });
+            events.push(EscalationEvent {
+                id: self.config.id.clone(),
+                title: self.config.title.clone(),
+                task_ids: pending.task_ids.clone(),
+                action: step.action,
+                step: pending.fired.len() - 1,
+                unanswered_seconds,
+                lock_duration: pending.lock_duration,
+            });
+        }
+        events
+    }
+
+    fn resumes_at(&self, now: Now) -> Option<i64> {
+        if !self.off_hours {
+            return None;
+        }
+        let hours = self.config.active_hours.as_ref()?;
+        hours.next_start(now.wall).map(|at| at.timestamp_millis())
+    }
+}
+
+pub struct Scheduler {
+    tasks: HashMap<String, TaskTimer>,
+    paused: bool,
+    pause_start: Option<Instant>,  // 停顿开始时间，暂停、系统锁屏、休息锁屏和免打扰共用，用于补偿
+    pause_until: Option<DateTime<Utc>>,  // 定时暂停的自动恢复时间，None 为需手动恢复
+    system_locked: bool,
+    lock_screen_active: bool,
+    // 免打扰相关
+    quiet_hours: Option<QuietHours>,
+    quiet_policy: QuietPolicy,
+    in_quiet: bool,  // 当前是否处于免打扰时段
+    collapse_due: bool,  // 免打扰刚结束，结束后的第一次计时把当时到期的任务合并为一次提醒
+    // 全屏推迟相关
+    fullscreen_policy: FullscreenPolicy,
+    max_defer_seconds: Option<u64>,  // 最长推迟秒数，None 为不限
+    deferred_since: Option<Instant>,  // 开始推迟到期提醒的时间点
+    deferred_reason: Option<DeferReason>,  // 当前推迟的原因，变化时重新通知
+    call_detection: bool,  // 通话时推迟提醒
+    call_max_defer_seconds: Option<u64>,  // 通话时最长推迟秒数，None 为推迟到通话结束
+    app_rules: AppRules,
+    // 空闲检测相关
+    idle_threshold_seconds: u64,  // 空闲阈值（秒），默认 300 秒 = 5 分钟
+    is_idle: bool,  // 当前是否处于空闲状态（仅按输入判断）
+    inhibited_since: Option<Instant>,  // 开始抑制屏保的时间点
+    // 重启恢复相关
+    offline_break_seconds: u64,  // 离线超过该时长视为已休息，默认 300 秒
+    // 提醒合并相关
+    merge_window_seconds: Option<u64>,  // 即将在该秒数内到期的任务合并到同一次提醒，None 为不合并
+    strict_mode: bool,  // 严格模式，只有规则允许的任务才能推迟
+    pending_restore: Option<TimerSnapshot>,  // 等待首次同步任务时恢复的进度
+    // 休眠检测相关
+    last_seen: Option<Now>,  // 上一次休眠检测的时间点
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self {
+            tasks: HashMap::new(),
+            paused: false,
+            pause_start: None,
+            pause_until: None,
+            system_locked: false,
+            lock_screen_active: false,
+            quiet_hours: None,
+            quiet_policy: QuietPolicy::default(),
+            in_quiet: false,
+            collapse_due: false,
+            fullscreen_policy: FullscreenPolicy::default(),
+            max_defer_seconds: Some(1800),
+            deferred_since: None,
+            deferred_reason: None,
+            call_detection: true,
+            call_max_defer_seconds: Some(3600),
+            app_rules: AppRules::default(),
+            idle_threshold_seconds: 300,  // 默认 5 分钟
+            is_idle: false,
+            inhibited_since: None,
+            offline_break_seconds: 300,
+            merge_window_seconds: Some(60),
+            strict_mode: false,
+            pending_restore: None,
+            last_seen: None,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// 按 id 排序的任务配置列表
+    pub fn task_configs(&self) -> Vec<TaskConfig> {
+        let mut tasks: Vec<TaskConfig> = self.tasks.values().map(|t| t.config.clone()).collect();
+        tasks.sort_by(|a, b| a.id.cmp(&b.id));
+        tasks
+    }
+
+    pub fn sync_tasks(&mut self, tasks: Vec<TaskConfig>, now: Now) {
+        // 保留现有任务的计时状态，只更新配置
+        let mut new_tasks: HashMap<String, TaskTimer> = HashMap::new();
+        let restore = self.pending_restore.take();
+
+        for task in tasks {
+            let timer = match self.tasks.remove(&task.id) {
+                // 新任务，如有上次保存的进度则恢复
+                None => {
+                    let mut timer = TaskTimer::new(task, now);
+                    if let Some(snapshot) = &restore {
+                        self.restore_timer(&mut timer, snapshot, now);
+                    }
+                    timer
+                }
+                // interval 或日程变了，重置计时
+                Some(existing)
+                    if existing.config.interval != task.interval
+                        || existing.config.schedule != task.schedule =>
+                {
+                    TaskTimer::new(task, now)
+                }
+                Some(mut existing) => {
+                    // 启用状态或活动时段变化时冻结/补偿倒计时
+                    existing.config = task;
+                    if existing.config.count_mode == CountMode::WallClock {
+                        existing.inactive = false;
+                    }
+                    existing.update_freeze(now, 0);
+                    existing
+                }
+            };
+            new_tasks.insert(timer.config.id.clone(), timer);
+        }
+
+        self.tasks = new_tasks;
+    }
+
+    /// 保存当前进度；上次的进度尚未恢复时返回 None，避免覆盖
+    pub fn snapshot(&self, now: Now) -> Option<TimerSnapshot> {
+        if self.pending_restore.is_some() {
+            return None;
+        }
+        let mut tasks: Vec<TaskSnapshot> = self.tasks.values().map(|t| t.snapshot(self.pause_start, now)).collect();
+        tasks.sort_by(|a, b| a.id.cmp(&b.id));
+        Some(TimerSnapshot {
+            saved_at: now.wall,
+            tasks,
+        })
+    }
+
+    /// 载入上次保存的进度，在下一次 sync_tasks 时应用到同 id 的任务
+    pub fn restore(&mut self, snapshot: TimerSnapshot) {
+        self.pending_restore = Some(snapshot);
+    }
+
+    fn restore_timer(&self, timer: &mut TaskTimer, snapshot: &TimerSnapshot, now: Now) {
+        let Some(saved) = snapshot.tasks.iter().find(|t| t.id == timer.config.id) else {
+            return;
+        };
+        // 间隔变化后旧进度没有意义
+        if saved.interval != timer.config.interval {
+            return;
+        }
+
+        let offline_secs = (now.wall - snapshot.saved_at).num_seconds().max(0) as u64;
+        if offline_secs >= self.offline_break_seconds {
+            // 离线足够久视为已休息：勾选「空闲重置」的任务从头开始，其余不计离线时间
+            if !timer.config.auto_reset_on_idle {
+                timer.restore(saved, 0, now);
+                // 离线期间错过的固定时刻不再补发
+                if timer.next_due.is_some_and(|due| due <= now.wall) {
+                    timer.schedule_next(now);
+                }
+            }
+        } else {
+            // 短暂离线（崩溃重启、安装更新）计入工作时间
+            timer.restore(saved, offline_secs, now);
+        }
+    }
+
+    pub fn set_offline_break_threshold(&mut self, seconds: u64) {
+        self.offline_break_seconds = seconds;
+    }
+
+    pub fn set_merge_window(&mut self, seconds: Option<u64>) {
+        self.merge_window_seconds = seconds;
+    }
+
+    pub fn set_fullscreen_policy(&mut self, policy: FullscreenPolicy, max_defer_seconds: Option<u64>) {
+        self.fullscreen_policy = policy;
+        self.max_defer_seconds = max_defer_seconds;
+    }
+
+    pub fn set_call_detection(&mut self, enabled: bool, max_defer_seconds: Option<u64>) {
+        self.call_detection = enabled;
+        self.call_max_defer_seconds = max_defer_seconds;
+    }
+
+    pub fn set_app_rules(&mut self, rules: Vec<AppRule>) {
+        self.app_rules.set_rules(rules);
+    }
+
+    /// 当前的活动窗口，没有启用全屏检测和应用规则时为 None
+    pub fn focused_window(&self) -> Option<&ActiveWindow> {
+        self.app_rules.focused()
+    }
+
+    /// 设置免打扰时段，下一次 tick 时生效；None 为关闭
+    pub fn set_quiet_hours(&mut self, quiet_hours: Option<QuietHours>, policy: QuietPolicy) {
+        self.quiet_hours = quiet_hours;
+        self.quiet_policy = policy;
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        self.in_quiet
+    }
+
+    /// 进入或离开免打扰时段。免打扰期间与暂停相同，离开时补偿停顿的时间，
+    /// 并按策略处理期间到期的固定时刻提醒
+    fn update_quiet(&mut self, now: Now) {
+        let quiet = self.quiet_hours.as_ref().is_some_and(|q| q.contains(now.wall));
+        if quiet == self.in_quiet {
+            return;
+        }
+        self.in_quiet = quiet;
+        if quiet {
+            self.suspend(now);
+            return;
+        }
+
+        if let Some(quiet_duration) = self.end_suspension(now) {
+            for timer in self.tasks.values_mut() {
+                timer.compensate(quiet_duration, now);
+            }
+        }
+
+        match self.quiet_policy {
+            QuietPolicy::Drop => {
+                for timer in self.tasks.values_mut() {
+                    if !timer.triggered && timer.fixed_times().is_some() && timer.is_due(now) {
+                        timer.restart(now);
+                    }
+                }
+            }
+            QuietPolicy::Defer => {}
+            QuietPolicy::Collapse => self.collapse_due = true,
+        }
+    }
+
+    pub fn pause(&mut self, now: Now) {
+        if !self.paused {
+            self.paused = true;
+            self.suspend(now);
+        }
+        self.pause_until = None;
+    }
+
+    fn is_suspended(&self) -> bool {
+        self.paused || self.system_locked || self.lock_screen_active || self.in_quiet
+    }
+
+    /// 开始停顿。暂停、系统锁屏、休息锁屏和免打扰共用一个起点，已因其他原因停顿时保留较早的起点
+    fn suspend(&mut self, now: Now) {
+        self.pause_start.get_or_insert(now.instant);
+    }
+
+    /// 某个停顿原因结束后调用，返回到现在为止需要补偿的时长。
+    /// 其他原因仍在时从现在起重新计算，重叠的部分只补偿一次
+    fn end_suspension(&mut self, now: Now) -> Option<Duration> {
+        let duration = self.pause_start.take().map(|start| now.instant.saturating_duration_since(start));
+        if self.is_suspended() {
+            self.pause_start = Some(now.instant);
+        }
+        duration
+    }
+
+    /// 暂停到指定时间后自动恢复；已暂停时只更新恢复时间
+    pub fn pause_until(&mut self, until: DateTime<Utc>, now: Now) {
+        self.pause(now);
+        self.pause_until = Some(until);
+    }
+
+    /// 定时暂停距自动恢复的秒数
+    pub fn pause_remaining(&self, now: Now) -> Option<u64> {
+        let until = self.pause_until.filter(|_| self.paused)?;
+        Some((until - now.wall).num_seconds().max(0) as u64)
+    }
+
+    /// 定时暂停到期时恢复计时，返回是否恢复
+    pub fn resume_if_due(&mut self, now: Now) -> bool {
+        if self.pause_until.is_some_and(|until| now.wall >= until) {
+            self.resume(now);
+            return true;
+        }
+        false
+    }
+
+    pub fn resume(&mut self, now: Now) {
+        if self.paused {
+            self.paused = false;
+            if let Some(pause_duration) = self.end_suspension(now) {
+                // 补偿暂停时间
+                for timer in self.tasks.values_mut() {
+                    timer.compensate(pause_duration, now);
+                }
+            }
+        }
+        self.pause_until = None;
+    }
+
+    pub fn reset_task(&mut self, task_id: &str, now: Now) {
+        if let Some(timer) = self.tasks.get_mut(task_id) {
+            timer.reset(now);
+        }
+    }
+
+    /// 完成一次休息：重置提醒中的任务以及它们抵扣的任务，返回重置的全部任务 id
+    pub fn complete_break(&mut self, task_ids: &[String], now: Now) -> Vec<String> {
+        let mut reset = task_ids.to_vec();
+        for id in task_ids {
+            for other in self.credited_by(id) {
+                if !reset.contains(&other) {
+                    reset.push(other);
+                }
+            }
+        }
+        for id in &reset {
+            self.reset_task(id, now);
+        }
+        reset
+    }
+
+    /// 提前结束休息（提前解锁或紧急解锁）：只让提醒中的任务重新计时，不抵扣其他任务，返回重置的任务 id
+    pub fn skip_break(&mut self, task_ids: &[String], now: Now) -> Vec<String> {
+        for id in task_ids {
+            self.reset_task(id, now);
+        }
+        task_ids.to_vec()
+    }
+
+    /// 完成该任务的休息时一并视为完成的任务，沿 satisfies 传递，不含自身
+    fn credited_by(&self, task_id: &str) -> Vec<String> {
+        let mut credited: Vec<String> = Vec::new();
+        let mut queue = vec![task_id];
+        while let Some(id) = queue.pop() {
+            let Some(timer) = self.tasks.get(id) else {
+                continue;
+            };
+            for other in &timer.config.satisfies {
+                if other != task_id && self.tasks.contains_key(other) && !credited.contains(other) {
+                    credited.push(other.clone());
+                    queue.push(other);
+                }
+            }
+        }
+        credited
+    }
+
+    /// 已触发、尚未处理的提醒中抵扣该任务的任务
+    fn covering_task(&self, task_id: &str) -> Option<&str> {
+        self.tasks.values()
+            .filter(|timer| timer.triggered && timer.config.id != task_id)
+            .find(|timer| self.credited_by(&timer.config.id).iter().any(|id| id == task_id))
+            .map(|timer| timer.config.id.as_str())
+    }
+
+    pub fn reset_all(&mut self, now: Now) {
+        for timer in self.tasks.values_mut() {
+            timer.reset(now);
+        }
+    }
+
+    /// 任务与合并在同一次提醒中的任务共同允许的推迟时长，以及当前不能推迟的原因。
+    /// 合并的任务一起推迟，只提供每个任务都允许的时长
+    pub fn snooze_state(&self, task_id: &str, now: Now) -> (Vec<u64>, Option<SnoozeRefusal>) {
+        let Some(task_ids) = self.merged_task_ids(task_id) else {
+            return (Vec::new(), Some(SnoozeRefusal::UnknownTask));
+        };
+        let timers: Vec<&TaskTimer> = task_ids.iter().filter_map(|id| self.tasks.get(id)).collect();
+        let options: Vec<u64> = timers[0].config.snooze.durations.iter()
+            .copied()
+            .filter(|m| timers.iter().all(|timer| timer.config.snooze.durations.contains(m)))
+            .collect();
+        let blocked = timers.iter()
+            .find_map(|timer| timer.check_snooze(None, self.strict_mode, now).err())
+            .or_else(|| options.is_empty().then_some(SnoozeRefusal::DurationNotAllowed));
+        (options, blocked)
+    }
+
+    /// 与任务合并在同一次提醒中的全部任务，第一个为任务本身；任务不存在时返回 None
+    fn merged_task_ids(&self, task_id: &str) -> Option<Vec<String>> {
+        let timer = self.tasks.get(task_id)?;
+        let mut task_ids = vec![task_id.to_string()];
+        if let Some(pending) = &timer.pending {
+            task_ids.extend(pending.task_ids.iter().filter(|id| *id != task_id && self.tasks.contains_key(*id)).cloned());
+        }
+        Some(task_ids)
+    }
+
+    pub fn is_strict_mode(&self) -> bool {
+        self.strict_mode
+    }
+
+    pub fn set_strict_mode(&mut self, enabled: bool) {
+        self.strict_mode = enabled;
+    }
+
+    /// 推迟任务及与其合并在同一次提醒中的任务，时长必须是每个任务都允许的；
+    /// 任一任务的规则不允许时全部不推迟。返回实际推迟的任务 id
+    pub fn snooze_task(&mut self, task_id: &str, minutes: u64, now: Now) -> Result<Vec<String>, SnoozeRefusal> {
+        let (options, blocked) = self.snooze_state(task_id, now);
+        if let Some(refusal) = blocked {
+            return Err(refusal);
+        }
+        if !options.contains(&minutes) {
+            return Err(SnoozeRefusal::DurationNotAllowed);
+        }
+        let task_ids = self.merged_task_ids(task_id).unwrap_or_default();
+        for id in &task_ids {
+            if let Some(timer) = self.tasks.get_mut(id) {
+                timer.snooze(minutes, now);
+            }
+        }
+        Ok(task_ids)
+    }
+
+    pub fn countdowns(&self, now: Now) -> Vec<CountdownInfo> {
+        // 暂停或锁屏期间倒计时停在开始的时间点，恢复时再整体补偿
+        let at = Now {
+            instant: self.pause_start.map_or(now.instant, |since| since.min(now.instant)),
+            wall: now.wall,
+        };
+        let pause_remaining = self.pause_remaining(now);
+        let quiet_until = self.quiet_hours.as_ref()
+            .filter(|_| self.in_quiet)
+            .and_then(|q| q.next_end(now.wall))
+            .map(|at| at.timestamp_millis());
+        self.tasks.values().map(|timer| {
+            let mut info = CountdownInfo {
+                pause_remaining,
+                quiet_until,
+                covered_by: (!timer.triggered).then(|| self.covering_task(&timer.config.id)).flatten().map(str::to_string),
+                ..timer.countdown(at, self.strict_mode)
+            };
+            // 已发出的提醒包含合并的任务时，推迟选项与 snooze_task 的检查一致
+            if timer.pending.as_ref().is_some_and(|pending| pending.task_ids.len() > 1) {
+                (info.snooze_options, info.snooze_blocked) = self.snooze_state(&timer.config.id, now);
+            }
+            info
+        }).collect()
+    }
+
+    /// 系统锁屏或解锁。休息锁屏期间会话监听同样会报告系统锁屏，两者共用停顿起点，重叠的时间只补偿一次
+    pub fn set_system_locked(&mut self, locked: bool, now: Now) {
+        if locked && !self.system_locked {
+            // 刚锁屏，记录暂停时间
+            self.system_locked = true;
+            self.suspend(now);
+        } else if !locked && self.system_locked {
+            // 解锁
+            self.system_locked = false;
+            let pause_duration = self.end_suspension(now);
+
+            for timer in self.tasks.values_mut() {
+                if timer.config.auto_reset_on_idle {
+                    // 勾选了"空闲重置"，直接重置为初始值
+                    timer.restart(now);
+                } else if let Some(duration) = pause_duration {
+                    // 没有勾选，补偿暂停时间
+                    timer.compensate(duration, now);
+                }
+            }
+        }
+    }
+
+    /// 检测系统休眠并按解锁规则处理，返回休眠的秒数。
+    /// 单调时钟在休眠期间不走（Linux、macOS），因此墙上时间多出的部分即为休眠时长。
+    pub fn detect_sleep(&mut self, now: Now) -> Option<u64> {
+        let last = self.last_seen.replace(now)?;
+        let wall_elapsed = (now.wall - last.wall).num_seconds();
+        let mono_elapsed = now.instant.saturating_duration_since(last.instant).as_secs() as i64;
+        let slept = wall_elapsed - mono_elapsed;
+        if slept < SUSPEND_GAP_SECONDS {
+            return None;
+        }
+
+        // 休眠视为空闲：勾选了「空闲重置」的任务重新开始，
+        // 其余任务无需补偿，单调时钟本身已不包含休眠时间
+        for timer in self.tasks.values_mut() {
+            if timer.config.auto_reset_on_idle {
+                timer.restart(now);
+            }
+        }
+        Some(slept as u64)
+    }
+
+    pub fn set_lock_screen_active(&mut self, active: bool, now: Now) {
+        if active && !self.lock_screen_active {
+            // 刚进入锁屏模式，记录开始时间
+            self.lock_screen_active = true;
+            self.suspend(now);
+        } else if !active && self.lock_screen_active {
+            // 退出锁屏模式，补偿锁屏期间的时间
+            self.lock_screen_active = false;
+            if let Some(lock_duration) = self.end_suspension(now) {
+                for timer in self.tasks.values_mut() {
+                    timer.compensate(lock_duration, now);
+                }
+            }
+        }
+    }
+
+    /// 可以触发的到期任务，按 id 排序；已触发的提醒完成后会一并重置的任务不单独提醒
+    fn due_tasks(&self, now: Now) -> Vec<String> {
+        let rules = &self.app_rules;
+        let mut due: Vec<String> = self.tasks.values()
+            .filter(|timer| timer.runnable() && self.covering_task(&timer.config.id).is_none())
+            .filter(|timer| timer.is_due(now) || rules.trigger_due(&timer.config.id, timer.reset_time, now.instant))
+            .map(|timer| timer.config.id.clone())
+            .collect();
+        due.sort();
+        due
+    }
+
+    /// 触发到期的任务；开启合并时，到期任务与即将到期的任务合并为一次提醒。
+    /// 通话中、前台全屏或应用规则要求推迟时暂不触发，推迟开始或原因变化时返回推迟事件。
+    /// collapse 为 true 时即使未开启合并，也把到期任务合并为一次提醒
+    fn trigger_due_tasks(
+        &mut self,
+        now: Now,
+        due: Vec<String>,
+        collapse: bool,
+        fullscreen: bool,
+        in_call: bool,
+    ) -> (Vec<TaskTriggeredPayload>, Option<ReminderDeferredPayload>) {
+        if due.is_empty() {
+            self.deferred_since = None;
+            self.deferred_reason = None;
+            return (Vec::new(), None);
+        }
+
+        let defer = if in_call {
+            Some((DeferReason::Call, None))
+        } else if fullscreen && self.fullscreen_policy == FullscreenPolicy::Defer {
+            Some((DeferReason::Fullscreen, None))
+        } else {
+            self.app_rules.defer_for(&due).map(|app| (DeferReason::App, Some(app)))
+        };
+        if let Some((reason, app)) = defer {
+            let changed = self.deferred_reason != Some(reason);
+            let since = *self.deferred_since.get_or_insert(now.instant);
+            let deferred_for = now.instant.saturating_duration_since(since).as_secs();
+            // 通话检测可能误报（虚拟摄像头、常驻的录音程序），通话也有单独的最长推迟时间
+            let max_defer = if reason == DeferReason::Call { self.call_max_defer_seconds } else { self.max_defer_seconds };
+            if max_defer.is_none_or(|max| deferred_for < max) {
+                self.deferred_reason = Some(reason);
+                let deferred = changed.then(|| ReminderDeferredPayload {
+                    task_ids: due,
+                    reason,
+                    until: max_defer
+                        .map(|max| (now.wall + chrono::Duration::seconds((max - deferred_for) as i64)).timestamp_millis()),
+                    app,
+                });
+                return (Vec::new(), deferred);
+            }
+        }
+        self.deferred_since = None;
+        self.deferred_reason = None;
+        let fullscreen_notify_only = fullscreen && self.fullscreen_policy == FullscreenPolicy::NotifyOnly;
+
+        let groups: Vec<Vec<String>> = match self.merge_window_seconds {
+            Some(window) => {
+                let mut upcoming: Vec<(u64, String)> = self.tasks.values()
+                    // 番茄钟的工作阶段不提前结束
+                    .filter(|timer| timer.runnable() && timer.pomodoro_config().is_none() && !due.contains(&timer.config.id))
+                    .map(|timer| (timer.countdown(now, self.strict_mode).remaining, timer.config.id.clone()))
+                    .filter(|(remaining, _)| *remaining <= window)
+                    .collect();
+                upcoming.sort();
+                let mut group = due;
+                group.extend(upcoming.into_iter().map(|(_, id)| id));
+                self.group_by_credit(group, true)
+            }
+            None if collapse => self.group_by_credit(due, true),
+            None => self.group_by_credit(due, false),
+        };
+
+        let triggered = groups.into_iter().map(|task_ids| {
+            // 标记为已触发，等待用户操作（重置或推迟）；番茄钟进入休息阶段
+            for id in &task_ids {
+                if let Some(timer) = self.tasks.get_mut(id) {
+                    timer.triggered = true;
+                    timer.start_break(now);
+                }
+            }
+            let lock_duration = task_ids.iter()
+                .filter_map(|id| self.tasks.get(id)?.break_seconds())
+                .max();
+            let notify_only = fullscreen_notify_only || self.app_rules.notify_only(&task_ids);
+            let primary = self.tasks.get_mut(&task_ids[0]).unwrap();
+            primary.pending = Some(PendingReminder {
+                since: now.instant,
+                since_wall: now.wall,
+                task_ids: task_ids.clone(),
+                lock_duration,
+                fired: Vec::new(),
+            });
+            let config = &primary.config;
+            TaskTriggeredPayload {
+                id: config.id.clone(),
+                title: config.title.clone(),
+                desc: config.desc.clone(),
+                icon: config.icon.clone(),
+                task_ids,
+                notify_only,
+                lock_duration,
+            }
+        }).collect();
+        (triggered, None)
+    }
+
+    /// 按休息抵扣关系整理提醒：抵扣同批任务最多的任务排在最前，作为提醒的主任务（决定锁屏时长）。
+    /// merged 为 false 时每个任务单独提醒，但被其他到期任务抵扣的任务并入该任务的提醒
+    fn group_by_credit(&self, ids: Vec<String>, merged: bool) -> Vec<Vec<String>> {
+        let mut ranked: Vec<(String, Vec<String>)> = ids.iter()
+            .map(|id| {
+                let credited = self.credited_by(id).into_iter().filter(|other| ids.contains(other)).collect();
+                (id.clone(), credited)
+            })
+            .collect();
+        ranked.sort_by_key(|(_, credited)| std::cmp::Reverse(credited.len()));
+        if merged {
+            return vec![ranked.into_iter().map(|(id, _)| id).collect()];
+        }
+
+        let mut grouped: Vec<String> = Vec::new();
+        let mut groups = Vec::new();
+        for (id, credited) in ranked {
+            if grouped.contains(&id) {
+                continue;
+            }
+            let mut group = vec![id];
+            group.extend(credited.into_iter().filter(|other| !grouped.contains(other)));
+            grouped.extend(group.iter().cloned());
+            groups.push(group);
+        }
+        groups
+    }
+
+    pub fn idle_threshold(&self) -> u64 {
+        self.idle_threshold_seconds
+    }
+
+    pub fn set_idle_threshold(&mut self, seconds: u64) {
+        self.idle_threshold_seconds = seconds;
+    }
+
+    /// 推进一次计时。暂停、系统锁屏、免打扰或锁屏模式激活时返回 None，且不会读取空闲时间。
+    pub fn tick(
+        &mut self,
+        now: Now,
+        idle: &mut dyn IdleSource,
+        inhibit: &mut dyn InhibitSource,
+        window: &mut dyn WindowSource,
+        call: &mut dyn CallSource,
+    ) -> Option<TickOutput> {
+        self.update_quiet(now);
+        // 免打扰结束后只合并第一次计时时到期的任务，之后到期的任务照常提醒
+        let collapse = std::mem::take(&mut self.collapse_due);
+        if self.is_suspended() {
+            return None;
+        }
+
+        // 没有可用的空闲检测后端时视为一直活跃
+        let idle_seconds = idle.idle_seconds().unwrap_or(0);
+        let inhibited = inhibit.is_inhibited();
+        let threshold = self.idle_threshold_seconds;
+        let is_now_idle = idle_seconds >= threshold;
+        let idle_status_changed = is_now_idle != self.is_idle || inhibited != self.inhibited_since.is_some();
+        self.is_idle = is_now_idle;
+        self.inhibited_since = if inhibited { self.inhibited_since.or(Some(now.instant)) } else { None };
+
+        // 只在启用全屏检测或应用规则时读取活动窗口
+        let active_window = if self.fullscreen_policy != FullscreenPolicy::Ignore || !self.app_rules.is_empty() {
+            window.active_window()
+        } else {
+            None
+        };
+        let fullscreen = self.fullscreen_policy != FullscreenPolicy::Ignore
+            && active_window.as_ref().is_some_and(|w| w.fullscreen);
+        self.app_rules.update_focus(active_window, now.instant);
+
+        let mut satisfied = Vec::new();
+
+        for timer in self.tasks.values_mut() {
+            timer.app_paused = self.app_rules.holds_timer(&timer.config.id);
+            // 屏保被抑制时按任务的策略决定空闲时长；抑制本身不算休息，只决定是否冻结
+            let (task_idle_seconds, passive) = match (inhibited, timer.config.inhibit_policy) {
+                (false, _) => (idle_seconds, false),
+                (true, InhibitPolicy::Passive) => (0, true),
+                (true, InhibitPolicy::Idle) => (idle_seconds, idle_seconds >= ACTIVE_INPUT_GRACE_SECONDS),
+                (true, InhibitPolicy::Active) => (0, false),
+            };
+            // 每个任务按自己的阈值判断是否已休息
+            let task_idle = task_idle_seconds >= timer.config.idle_threshold.unwrap_or(threshold);
+            timer.passive = passive;
+            // 按有效时间计时的间隔任务，只在有输入（或视为工作）时推进
+            let working = !task_idle
+                && (idle_seconds < ACTIVE_INPUT_GRACE_SECONDS
+                    || (inhibited && timer.config.inhibit_policy == InhibitPolicy::Active));
+            timer.inactive = timer.config.count_mode == CountMode::ActiveTime
+                && timer.fixed_times().is_none()
+                && !working;
+
+            // 进入或离开空闲状态时，勾选了「空闲重置」的任务从头开始
+            if task_idle != timer.idle {
+                timer.idle = task_idle;
+                if timer.config.auto_reset_on_idle && timer.config.enabled {
+                    timer.restart(now);
+                    if task_idle {
+                        satisfied.push(timer.config.id.clone());
+                    }
+                }
+            }
+
+            // 进入或离开活动时段、被动计时或无输入时冻结/恢复倒计时
+            timer.update_freeze(now, idle_seconds);
+        }
+
+        let due = self.due_tasks(now);
+        // 只在有提醒等待时检测通话，避免每秒扫描
+        let waiting = !due.is_empty() || self.tasks.values().any(|timer| timer.pending.is_some());
+        let in_call = self.call_detection && waiting && call.in_call();
+        let (triggered, deferred) = self.trigger_due_tasks(now, due, collapse, fullscreen, in_call);
+
+        // 用户离开、任务冻结、通话中、前台全屏或应用规则要求推迟时暂缓升级，之后再执行
+        let rules = &self.app_rules;
+        let mut escalations: Vec<EscalationEvent> = self.tasks.values_mut()
+            .filter(|timer| !in_call && !fullscreen && !rules.holds_reminder(&timer.config.id))
+            .filter(|timer| !timer.idle && timer.frozen_at.is_none())
+            .flat_map(|timer| timer.escalate(now))
+            .collect();
+        escalations.sort_by(|a, b| a.id.cmp(&b.id).then(a.step.cmp(&b.step)));
+
+        let idle_status = idle_status_changed.then_some(IdleStatus {
+            is_idle: self.is_idle,
+            idle_seconds,
+            threshold,
+            inhibited,
+        });
+
+        satisfied.sort();
+        let natural_break = (!satisfied.is_empty()).then_some(NaturalBreakPayload {
+            task_ids: satisfied,
+            idle_seconds,
+        });
+
+        Some(TickOutput {
+            triggered,
+            natural_break,
+            escalations,
+            deferred,
+            idle_status,
+            countdowns: self.countdowns(now),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, TimeZone};
+
+    struct FakeIdle(u64);
+
+    impl IdleSource for FakeIdle {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn idle_seconds(&mut self) -> Option<u64> {
+            Some(self.0)
+        }
+    }
+
+    struct FakeInhibit(bool);
+
+    impl InhibitSource for FakeInhibit {
+        fn is_inhibited(&mut self) -> bool {
+            self.0
+        }
+    }
+
+    struct FakeWindow(Option<ActiveWindow>);
+
+    impl WindowSource for FakeWindow {
+        fn active_window(&mut self) -> Option<ActiveWindow> {
+            self.0.clone()
+        }
+    }
+
+    struct FakeCall(bool);
+
+    impl CallSource for FakeCall {
+        fn in_call(&mut self) -> bool {
+            self.0
+        }
+    }
+
+    /// 从固定起点开始的可控时钟
+    struct TestClock {
+        instant: Instant,
+        wall: DateTime<Utc>,
+    }
+
+    impl TestClock {
+        fn new() -> Self {
+            Self::at_wall(Utc::now())
+        }
+
+        fn at_wall(wall: DateTime<Utc>) -> Self {
+            Self { instant: Instant::now(), wall }
+        }
+
+        fn at(&self, seconds: u64) -> Now {
+            Now {
+                instant: self.instant + Duration::from_secs(seconds),
+                wall: self.wall + chrono::Duration::seconds(seconds as i64),
+            }
+        }
+    }
+
+    /// 东八区 2026-10-16（周五）的本地时间
+    fn friday(hour: u32, minute: u32) -> DateTime<Utc> {
+        FixedOffset::east_opt(8 * 3600).unwrap()
+            .with_ymd_and_hms(2026, 10, 16, hour, minute, 0).unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn task(id: &str, minutes: u64) -> TaskConfig {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "title": id, "desc": "", "interval": minutes, "enabled": true, "icon": "",
+        })).unwrap()
+    }
+
+    fn tick(scheduler: &mut Scheduler, now: Now, idle: u64, inhibited: bool) -> Option<TickOutput> {
+        scheduler.tick(now, &mut FakeIdle(idle), &mut FakeInhibit(inhibited), &mut FakeWindow(None), &mut FakeCall(false))
+    }
+
+    fn remaining(scheduler: &Scheduler, id: &str, now: Now) -> u64 {
+        scheduler.countdowns(now).into_iter().find(|c| c.id == id).unwrap().remaining
+    }
+
+    fn triggered_ids(output: &TickOutput) -> Vec<String> {
+        output.triggered.iter().map(|t| t.id.clone()).collect()
+    }
+
+    #[test]
+    fn interval_task_triggers_after_interval() {
+        let clock = TestClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![task("a", 10)], clock.at(0));
+
+        let output = tick(&mut scheduler, clock.at(599), 0, false).unwrap();
+        assert!(output.triggered.is_empty());
+        assert_eq!(output.countdowns[0].remaining, 1);
+        let output = tick(&mut scheduler, clock.at(600), 0, false).unwrap();
+        assert_eq!(triggered_ids(&output), ["a"]);
+    }
+
+    #[test]
+    fn pause_and_resume_compensate_paused_time() {
+        let clock = TestClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![task("a", 10)], clock.at(0));
+
+        tick(&mut scheduler, clock.at(60), 0, false);
+        scheduler.pause(clock.at(60));
+        assert!(tick(&mut scheduler, clock.at(120), 0, false).is_none());
+        assert_eq!(remaining(&scheduler, "a", clock.at(300)), 540);
+        scheduler.resume(clock.at(360));
+        assert_eq!(remaining(&scheduler, "a", clock.at(360)), 540);
+    }
+
+    #[test]
+    fn timed_pause_resumes_when_due() {
+        let clock = TestClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![task("a", 10)], clock.at(0));
+
+        scheduler.pause_until(clock.at(300).wall, clock.at(0));
+        assert_eq!(scheduler.pause_remaining(clock.at(100)), Some(200));
+        assert!(!scheduler.resume_if_due(clock.at(299)));
+        assert!(scheduler.resume_if_due(clock.at(300)));
+        assert_eq!(remaining(&scheduler, "a", clock.at(300)), 600);
+    }
+
+    #[test]
+    fn system_lock_resets_or_compensates() {
+        let clock = TestClock::new();
+        let mut reset = task("reset", 10);
+        reset.auto_reset_on_idle = true;
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![reset, task("keep", 10)], clock.at(0));
+
+        tick(&mut scheduler, clock.at(120), 0, false);
+        scheduler.set_system_locked(true, clock.at(120));
+        assert!(tick(&mut scheduler, clock.at(200), 0, false).is_none());
+        scheduler.set_system_locked(false, clock.at(420));
+        assert_eq!(remaining(&scheduler, "reset", clock.at(420)), 600);
+        assert_eq!(remaining(&scheduler, "keep", clock.at(420)), 480);
+    }
+
+    #[test]
+    fn sleep_restarts_auto_reset_tasks_and_is_not_counted() {
+        let clock = TestClock::new();
+        let mut reset = task("reset", 10);
+        reset.auto_reset_on_idle = true;
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![reset, task("keep", 10)], clock.at(0));
+
+        assert_eq!(scheduler.detect_sleep(clock.at(0)), None);
+        // 两次检测之间墙上时间与单调时钟的差距不足时不视为休眠
+        let short = Now { wall: clock.at(30).wall + chrono::Duration::seconds(SUSPEND_GAP_SECONDS - 1), ..clock.at(30) };
+        assert_eq!(scheduler.detect_sleep(short), None);
+        assert_eq!(remaining(&scheduler, "reset", clock.at(30)), 570);
+
+        // 单调时钟在休眠期间不走，墙上时间多出一小时
+        let woke = Now { wall: short.wall + chrono::Duration::seconds(30 + 3600), ..clock.at(60) };
+        assert_eq!(scheduler.detect_sleep(woke), Some(3600));
+        assert_eq!(remaining(&scheduler, "reset", clock.at(60)), 600);
+        assert_eq!(remaining(&scheduler, "keep", clock.at(60)), 540);
+    }
+
+    #[test]
+    fn idle_resets_tasks_and_reports_natural_break() {
+        let clock = TestClock::new();
+        let mut reset = task("reset", 10);
+        reset.auto_reset_on_idle = true;
+        let mut scheduler = Scheduler::new();
+        scheduler.set_idle_threshold(60);
+        scheduler.sync_tasks(vec![reset, task("keep", 10)], clock.at(0));
+
+        tick(&mut scheduler, clock.at(300), 0, false);
+        let output = tick(&mut scheduler, clock.at(360), 60, false).unwrap();
+        assert!(output.idle_status.unwrap().is_idle);
+        assert_eq!(output.natural_break.unwrap().task_ids, ["reset"]);
+        // 空闲期间不触发，回来后从头计时
+        let output = tick(&mut scheduler, clock.at(700), 400, false).unwrap();
+        assert!(output.triggered.is_empty());
+        tick(&mut scheduler, clock.at(701), 0, false);
+        assert_eq!(remaining(&scheduler, "reset", clock.at(701)), 600);
+    }
+
+    fn quiet_10_10_to_10_40() -> QuietHours {
+        serde_json::from_value(serde_json::json!({
+            "windows": [{ "start": "10:10", "end": "10:40" }], "timezone": "+08:00",
+        })).unwrap()
+    }
+
+    #[test]
+    fn pause_during_quiet_hours_keeps_earliest_start() {
+        let clock = TestClock::at_wall(friday(10, 0));
+        let mut scheduler = Scheduler::new();
+        scheduler.set_quiet_hours(Some(quiet_10_10_to_10_40()), QuietPolicy::Defer);
+        scheduler.sync_tasks(vec![task("a", 60)], clock.at(0));
+
+        tick(&mut scheduler, clock.at(600), 0, false);
+        scheduler.pause(clock.at(1200));
+        tick(&mut scheduler, clock.at(2700), 0, false);
+        assert_eq!(remaining(&scheduler, "a", clock.at(2700)), 3000);
+        scheduler.resume(clock.at(3000));
+        assert_eq!(remaining(&scheduler, "a", clock.at(3000)), 3000);
+    }
+
+    #[test]
+    fn system_lock_during_pause_keeps_earliest_start() {
+        let clock = TestClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![task("a", 60)], clock.at(0));
+
+        scheduler.pause(clock.at(600));
+        scheduler.set_system_locked(true, clock.at(900));
+        scheduler.resume(clock.at(1200));
+        assert_eq!(remaining(&scheduler, "a", clock.at(1500)), 3000);
+        scheduler.set_system_locked(false, clock.at(1800));
+        assert_eq!(remaining(&scheduler, "a", clock.at(1800)), 3000);
+    }
+
+    #[test]
+    fn lock_screen_during_pause_is_compensated_once() {
+        let clock = TestClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![task("a", 10)], clock.at(0));
+
+        scheduler.pause(clock.at(60));
+        scheduler.set_lock_screen_active(true, clock.at(120));
+        assert_eq!(remaining(&scheduler, "a", clock.at(150)), 540);
+        scheduler.set_lock_screen_active(false, clock.at(180));
+        assert_eq!(remaining(&scheduler, "a", clock.at(200)), 540);
+        scheduler.resume(clock.at(240));
+        assert_eq!(remaining(&scheduler, "a", clock.at(240)), 540);
+    }
+
+    #[test]
+    fn pause_during_lock_screen_is_compensated_once() {
+        let clock = TestClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![task("a", 10)], clock.at(0));
+
+        scheduler.set_lock_screen_active(true, clock.at(60));
+        scheduler.pause(clock.at(120));
+        scheduler.set_lock_screen_active(false, clock.at(240));
+        scheduler.resume(clock.at(300));
+        assert_eq!(remaining(&scheduler, "a", clock.at(300)), 540);
+    }
+
+    #[test]
+    fn system_lock_during_lock_screen_is_compensated_once() {
+        let clock = TestClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![task("water", 60)], clock.at(0));
+
+        scheduler.set_lock_screen_active(true, clock.at(600));
+        scheduler.set_system_locked(true, clock.at(700));
+        scheduler.set_system_locked(false, clock.at(1000));
+        assert_eq!(remaining(&scheduler, "water", clock.at(1100)), 3000);
+        scheduler.set_lock_screen_active(false, clock.at(1200));
+        assert_eq!(remaining(&scheduler, "water", clock.at(1200)), 3000);
+    }
+
+    fn fixed_at(id: &str, time: &str) -> TaskConfig {
+        let mut fixed = task(id, 10);
+        fixed.schedule = serde_json::from_value(serde_json::json!({
+            "type": "fixed", "times": [time], "timezone": "+08:00",
+        })).unwrap();
+        fixed
+    }
+
+    #[test]
+    fn collapse_merges_only_tasks_due_when_quiet_ends() {
+        let clock = TestClock::at_wall(friday(10, 0));
+        let mut scheduler = Scheduler::new();
+        scheduler.set_merge_window(None);
+        scheduler.set_quiet_hours(Some(quiet_10_10_to_10_40()), QuietPolicy::Collapse);
+        scheduler.sync_tasks(
+            vec![fixed_at("f1", "10:20"), fixed_at("f2", "10:30"), task("i1", 60), task("i2", 60)],
+            clock.at(0),
+        );
+
+        tick(&mut scheduler, clock.at(600), 0, false);
+        let output = tick(&mut scheduler, clock.at(2400), 0, false).unwrap();
+        assert_eq!(output.triggered.len(), 1);
+        assert_eq!(output.triggered[0].task_ids.len(), 2);
+        // 免打扰的 30 分钟已补偿，间隔任务在 11:30 到期，不再合并
+        let output = tick(&mut scheduler, clock.at(5400), 0, false).unwrap();
+        assert_eq!(output.triggered.len(), 2);
+    }
+
+    #[test]
+    fn collapse_flag_clears_when_nothing_was_due() {
+        let clock = TestClock::at_wall(friday(10, 0));
+        let mut scheduler = Scheduler::new();
+        scheduler.set_merge_window(None);
+        scheduler.set_quiet_hours(Some(quiet_10_10_to_10_40()), QuietPolicy::Collapse);
+        scheduler.sync_tasks(vec![task("i1", 60), task("i2", 60)], clock.at(0));
+
+        tick(&mut scheduler, clock.at(600), 0, false);
+        assert!(tick(&mut scheduler, clock.at(2400), 0, false).unwrap().triggered.is_empty());
+        let output = tick(&mut scheduler, clock.at(5400), 0, false).unwrap();
+        assert_eq!(output.triggered.len(), 2);
+    }
+
+    #[test]
+    fn inhibitor_does_not_count_as_idle_while_typing() {
+        let clock = TestClock::new();
+        let mut a = task("a", 10);
+        a.auto_reset_on_idle = true;
+        a.inhibit_policy = InhibitPolicy::Idle;
+        let mut scheduler = Scheduler::new();
+        scheduler.set_idle_threshold(60);
+        scheduler.sync_tasks(vec![a], clock.at(0));
+
+        for second in (10..600).step_by(10) {
+            let output = tick(&mut scheduler, clock.at(second), 0, true).unwrap();
+            assert!(output.natural_break.is_none());
+            assert!(output.triggered.is_empty());
+        }
+        let output = tick(&mut scheduler, clock.at(600), 0, true).unwrap();
+        assert_eq!(triggered_ids(&output), ["a"]);
+    }
+
+    #[test]
+    fn inhibitor_freezes_timer_without_input() {
+        let clock = TestClock::new();
+        let mut a = task("a", 10);
+        a.inhibit_policy = InhibitPolicy::Idle;
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![a], clock.at(0));
+
+        tick(&mut scheduler, clock.at(60), 0, true);
+        tick(&mut scheduler, clock.at(70), 10, true);
+        tick(&mut scheduler, clock.at(170), 110, true);
+        assert_eq!(remaining(&scheduler, "a", clock.at(170)), 530);
+    }
+
+    #[test]
+    fn skipped_break_does_not_credit_other_tasks() {
+        let clock = TestClock::new();
+        let mut sit = task("sit", 10);
+        sit.satisfies = vec!["eye".to_string()];
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![sit, task("eye", 20)], clock.at(0));
+
+        tick(&mut scheduler, clock.at(600), 0, false);
+        assert_eq!(scheduler.skip_break(&["sit".to_string()], clock.at(660)), ["sit"]);
+        assert_eq!(remaining(&scheduler, "sit", clock.at(660)), 600);
+        assert_eq!(remaining(&scheduler, "eye", clock.at(660)), 540);
+
+        tick(&mut scheduler, clock.at(1260), 0, false);
+        assert_eq!(scheduler.complete_break(&["sit".to_string()], clock.at(1320)), ["sit", "eye"]);
+        assert_eq!(remaining(&scheduler, "eye", clock.at(1320)), 1200);
+    }
+
+    #[test]
+    fn escalation_to_lock_screen_keeps_pomodoro_break_length() {
+        let clock = TestClock::new();
+        let mut pomodoro = task("pomodoro", 25);
+        pomodoro.schedule = serde_json::from_value(serde_json::json!({
+            "type": "pomodoro", "short_break": 5, "long_break": 15, "long_break_every": 4,
+        })).unwrap();
+        pomodoro.escalation = vec![EscalationStep { after_seconds: 60, action: EscalationAction::LockScreen }];
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![pomodoro], clock.at(0));
+
+        let output = tick(&mut scheduler, clock.at(1500), 0, false).unwrap();
+        assert_eq!(output.triggered[0].lock_duration, Some(300));
+        let output = tick(&mut scheduler, clock.at(1560), 0, false).unwrap();
+        assert_eq!(output.escalations[0].action, EscalationAction::LockScreen);
+        assert_eq!(output.escalations[0].lock_duration, Some(300));
+    }
+
+    #[test]
+    fn only_while_rule_runs_when_active_window_is_unknown() {
+        let clock = TestClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.set_app_rules(vec![AppRule {
+            apps: vec!["code".to_string()],
+            task_ids: Vec::new(),
+            action: crate::app_rules::AppRuleAction::OnlyWhile,
+        }]);
+        scheduler.sync_tasks(vec![task("a", 10)], clock.at(0));
+        let browser = ActiveWindow { class: Some("firefox".to_string()), ..Default::default() };
+
+        let at = |scheduler: &mut Scheduler, seconds, window: Option<ActiveWindow>| {
+            scheduler.tick(clock.at(seconds), &mut FakeIdle(0), &mut FakeInhibit(false), &mut FakeWindow(window), &mut FakeCall(false)).unwrap()
+        };
+        at(&mut scheduler, 60, None);
+        at(&mut scheduler, 120, Some(browser.clone()));
+        let output = at(&mut scheduler, 180, Some(browser));
+        assert!(output.countdowns[0].app_paused);
+        assert_eq!(output.countdowns[0].remaining, 480);
+        let output = at(&mut scheduler, 240, None);
+        assert!(!output.countdowns[0].app_paused);
+    }
+
+    #[test]
+    fn snooze_respects_durations_and_limits() {
+        let clock = TestClock::new();
+        let mut a = task("a", 10);
+        a.snooze = SnoozePolicy { max_count: 1, durations: vec![5], cooldown_seconds: 0, allow_in_strict: false };
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![a], clock.at(0));
+
+        tick(&mut scheduler, clock.at(600), 0, false);
+        assert_eq!(scheduler.snooze_task("a", 3, clock.at(600)), Err(SnoozeRefusal::DurationNotAllowed));
+        assert_eq!(scheduler.snooze_task("a", 5, clock.at(600)), Ok(vec!["a".to_string()]));
+        assert_eq!(remaining(&scheduler, "a", clock.at(600)), 300);
+
+        let output = tick(&mut scheduler, clock.at(900), 0, false).unwrap();
+        assert_eq!(triggered_ids(&output), ["a"]);
+        assert_eq!(scheduler.snooze_state("a", clock.at(900)).1, Some(SnoozeRefusal::LimitReached));
+        assert_eq!(scheduler.snooze_task("a", 5, clock.at(900)), Err(SnoozeRefusal::LimitReached));
+    }
+
+    #[test]
+    fn merged_reminder_snoozes_with_shared_durations() {
+        let clock = TestClock::new();
+        let mut sit = task("sit", 10);
+        sit.snooze.durations = vec![5, 10];
+        let mut eye = task("eye", 10);
+        eye.snooze.durations = vec![2, 5];
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![sit, eye], clock.at(0));
+
+        let output = tick(&mut scheduler, clock.at(600), 0, false).unwrap();
+        let primary = output.triggered[0].id.clone();
+        assert_eq!(output.triggered[0].task_ids.len(), 2);
+        assert_eq!(scheduler.snooze_state(&primary, clock.at(600)), (vec![5], None));
+        assert_eq!(scheduler.snooze_task(&primary, 10, clock.at(600)), Err(SnoozeRefusal::DurationNotAllowed));
+        assert_eq!(scheduler.snooze_task(&primary, 5, clock.at(600)).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn merged_reminder_without_shared_duration_is_blocked() {
+        let clock = TestClock::new();
+        let mut sit = task("sit", 10);
+        sit.snooze.durations = vec![5];
+        let mut eye = task("eye", 10);
+        eye.snooze.durations = vec![2];
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![sit, eye], clock.at(0));
+
+        let output = tick(&mut scheduler, clock.at(600), 0, false).unwrap();
+        let primary = output.triggered[0].id.clone();
+        assert_eq!(scheduler.snooze_state(&primary, clock.at(600)), (vec![], Some(SnoozeRefusal::DurationNotAllowed)));
+        let info = scheduler.countdowns(clock.at(600)).into_iter().find(|c| c.id == primary).unwrap();
+        assert_eq!(info.snooze_blocked, Some(SnoozeRefusal::DurationNotAllowed));
+    }
+
+    #[test]
+    fn snooze_refused_in_strict_mode_unless_allowed() {
+        let clock = TestClock::new();
+        let mut allowed = task("allowed", 10);
+        allowed.snooze.allow_in_strict = true;
+        let mut scheduler = Scheduler::new();
+        scheduler.set_strict_mode(true);
+        scheduler.set_merge_window(None);
+        scheduler.sync_tasks(vec![allowed, task("strict", 10)], clock.at(0));
+
+        tick(&mut scheduler, clock.at(600), 0, false);
+        assert_eq!(scheduler.snooze_task("strict", 5, clock.at(600)), Err(SnoozeRefusal::StrictMode));
+        assert!(scheduler.snooze_task("allowed", 5, clock.at(600)).is_ok());
+    }
+
+    #[test]
+    fn call_defers_reminder_up_to_the_limit() {
+        let clock = TestClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.set_call_detection(true, Some(600));
+        scheduler.sync_tasks(vec![task("a", 10)], clock.at(0));
+
+        let in_call = |scheduler: &mut Scheduler, seconds| {
+            scheduler.tick(clock.at(seconds), &mut FakeIdle(0), &mut FakeInhibit(false), &mut FakeWindow(None), &mut FakeCall(true)).unwrap()
+        };
+        let output = in_call(&mut scheduler, 600);
+        assert!(output.triggered.is_empty());
+        assert_eq!(output.deferred.unwrap().reason, DeferReason::Call);
+        assert!(in_call(&mut scheduler, 1199).triggered.is_empty());
+        assert_eq!(triggered_ids(&in_call(&mut scheduler, 1200)), ["a"]);
+    }
+
+    #[test]
+    fn fixed_schedule_triggers_at_listed_times() {
+        let clock = TestClock::at_wall(friday(10, 0));
+        let mut fixed = task("fixed", 45);
+        fixed.schedule = serde_json::from_value(serde_json::json!({
+            "type": "fixed", "times": ["15:30", "10:30"], "weekdays": 31, "timezone": "+08:00",
+        })).unwrap();
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![fixed], clock.at(0));
+
+        let output = tick(&mut scheduler, clock.at(10), 0, false).unwrap();
+        assert_eq!(output.countdowns[0].remaining, 1790);
+        let output = tick(&mut scheduler, clock.at(1800), 0, false).unwrap();
+        assert_eq!(triggered_ids(&output), ["fixed"]);
+        // 完成后等到 15:30；周五 15:30 之后跳到周一 10:30
+        scheduler.reset_task("fixed", clock.at(1860));
+        assert_eq!(remaining(&scheduler, "fixed", clock.at(1860)), 5 * 3600 - 60);
+        scheduler.reset_task("fixed", clock.at(6 * 3600));
+        assert_eq!(remaining(&scheduler, "fixed", clock.at(6 * 3600)), 3 * 86400 - 6 * 3600 + 1800);
+    }
+
+    #[test]
+    fn quiet_hours_pause_and_compensate() {
+        let clock = TestClock::at_wall(friday(10, 0));
+        let mut scheduler = Scheduler::new();
+        scheduler.set_quiet_hours(Some(quiet_10_10_to_10_40()), QuietPolicy::Defer);
+        scheduler.sync_tasks(vec![task("a", 60)], clock.at(0));
+
+        tick(&mut scheduler, clock.at(300), 0, false);
+        assert!(tick(&mut scheduler, clock.at(600), 0, false).is_none());
+        assert!(scheduler.is_quiet());
+        assert!(tick(&mut scheduler, clock.at(2399), 0, false).is_none());
+        tick(&mut scheduler, clock.at(2400), 0, false).unwrap();
+        assert!(!scheduler.is_quiet());
+        // 免打扰的 30 分钟不计入
+        assert_eq!(remaining(&scheduler, "a", clock.at(2400)), 3600 - 600);
+    }
+
+    /// 保存进度后离线 offline_secs 秒，在新的调度器中恢复
+    fn restart_after(snapshot: TimerSnapshot, offline_secs: i64, tasks: Vec<TaskConfig>) -> (Scheduler, TestClock) {
+        let clock = TestClock::at_wall(snapshot.saved_at + chrono::Duration::seconds(offline_secs));
+        let mut scheduler = Scheduler::new();
+        scheduler.restore(snapshot);
+        scheduler.sync_tasks(tasks, clock.at(0));
+        (scheduler, clock)
+    }
+
+    #[test]
+    fn short_offline_time_counts_as_work() {
+        let clock = TestClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![task("a", 10)], clock.at(0));
+        let snapshot = scheduler.snapshot(clock.at(120)).unwrap();
+
+        let (restored, clock) = restart_after(snapshot, 60, vec![task("a", 10)]);
+        assert_eq!(remaining(&restored, "a", clock.at(0)), 420);
+    }
+
+    #[test]
+    fn long_offline_time_counts_as_break() {
+        let clock = TestClock::new();
+        let mut reset = task("reset", 10);
+        reset.auto_reset_on_idle = true;
+        let tasks = vec![reset, task("keep", 10)];
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(tasks.clone(), clock.at(0));
+        let snapshot = scheduler.snapshot(clock.at(120)).unwrap();
+
+        // 默认阈值 300 秒
+        let (restored, clock) = restart_after(snapshot, 300, tasks);
+        assert_eq!(remaining(&restored, "reset", clock.at(0)), 600);
+        assert_eq!(remaining(&restored, "keep", clock.at(0)), 480);
+    }
+
+    #[test]
+    fn paused_progress_is_saved_without_paused_time() {
+        let clock = TestClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![task("a", 10)], clock.at(0));
+        scheduler.pause(clock.at(120));
+        let snapshot = scheduler.snapshot(clock.at(400)).unwrap();
+        assert!(!snapshot.tasks[0].running);
+
+        let (restored, clock) = restart_after(snapshot, 60, vec![task("a", 10)]);
+        assert_eq!(remaining(&restored, "a", clock.at(0)), 480);
+    }
+
+    #[test]
+    fn snapshot_is_withheld_until_restored() {
+        let clock = TestClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![task("a", 10)], clock.at(0));
+        let snapshot = scheduler.snapshot(clock.at(120)).unwrap();
+
+        let mut restarted = Scheduler::new();
+        restarted.restore(snapshot);
+        assert!(restarted.snapshot(clock.at(0)).is_none());
+        restarted.sync_tasks(vec![task("a", 10)], clock.at(0));
+        assert!(restarted.snapshot(clock.at(0)).is_some());
+    }
+
+    #[test]
+    fn fixed_time_is_restored_and_missed_times_are_skipped() {
+        let clock = TestClock::at_wall(friday(10, 0));
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![fixed_at("fixed", "10:30")], clock.at(0));
+        let snapshot = scheduler.snapshot(clock.at(600)).unwrap();
+
+        let (restored, later) = restart_after(snapshot.clone(), 60, vec![fixed_at("fixed", "10:30")]);
+        assert_eq!(remaining(&restored, "fixed", later.at(0)), 1140);
+        // 离线期间错过了 10:30，不再补发，改为第二天的 10:30
+        let (restored, later) = restart_after(snapshot, 3600, vec![fixed_at("fixed", "10:30")]);
+        assert_eq!(remaining(&restored, "fixed", later.at(0)), 86400 - 2400);
+    }
+
+    #[test]
+    fn pomodoro_phase_survives_restart() {
+        let clock = TestClock::new();
+        let mut pomodoro = task("pomodoro", 25);
+        pomodoro.schedule = serde_json::from_value(serde_json::json!({
+            "type": "pomodoro", "short_break": 5, "long_break": 15, "long_break_every": 4,
+        })).unwrap();
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![pomodoro.clone()], clock.at(0));
+        tick(&mut scheduler, clock.at(1500), 0, false).unwrap();
+        let snapshot = scheduler.snapshot(clock.at(1510)).unwrap();
+
+        let (restored, clock) = restart_after(snapshot, 30, vec![pomodoro]);
+        let info = restored.countdowns(clock.at(0))[0].pomodoro.clone().unwrap();
+        assert_eq!(info.phase, PomodoroPhase::ShortBreak);
+        assert_eq!(info.completed, 1);
+        assert_eq!(info.break_seconds, 300);
+    }
+
+    #[test]
+    fn active_time_freezes_after_the_input_grace_period() {
+        let clock = TestClock::new();
+        let mut active = task("active", 10);
+        active.count_mode = CountMode::ActiveTime;
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(vec![active, task("wall", 10)], clock.at(0));
+
+        tick(&mut scheduler, clock.at(60), 0, false);
+        // 宽限期内的短暂停顿照常计时
+        let output = tick(&mut scheduler, clock.at(100), ACTIVE_INPUT_GRACE_SECONDS - 1, false).unwrap();
+        assert!(!output.countdowns.iter().any(|c| c.inactive));
+        assert_eq!(remaining(&scheduler, "active", clock.at(100)), 500);
+
+        // 最后一次输入在 96 秒，宽限期结束于 101 秒，从那时起冻结
+        tick(&mut scheduler, clock.at(130), 34, false);
+        let info = scheduler.countdowns(clock.at(130)).into_iter().find(|c| c.id == "active").unwrap();
+        assert!(info.inactive);
+        assert_eq!(info.remaining, 499);
+        assert_eq!(remaining(&scheduler, "wall", clock.at(130)), 470);
+
+        tick(&mut scheduler, clock.at(200), 0, false);
+        assert_eq!(remaining(&scheduler, "active", clock.at(200)), 499);
+        assert_eq!(remaining(&scheduler, "wall", clock.at(200)), 400);
+    }
+
+    #[test]
+    fn reminders_due_within_the_merge_window_are_grouped() {
+        let clock = TestClock::new();
+        let tasks = vec![task("a", 10), task("b", 11), task("c", 12)];
+        let mut scheduler = Scheduler::new();
+        scheduler.sync_tasks(tasks.clone(), clock.at(0));
+
+        // b 还有 60 秒到期，在默认的 60 秒窗口内；c 还有 120 秒
+        let output = tick(&mut scheduler, clock.at(600), 0, false).unwrap();
+        assert_eq!(output.triggered.len(), 1);
+        assert_eq!(output.triggered[0].id, "a");
+        assert_eq!(output.triggered[0].task_ids, ["a", "b"]);
+        assert_eq!(scheduler.complete_break(&output.triggered[0].task_ids, clock.at(630)), ["a", "b"]);
+        assert_eq!(remaining(&scheduler, "b", clock.at(630)), 660);
+        assert_eq!(remaining(&scheduler, "c", clock.at(630)), 90);
+
+        let mut separate = Scheduler::new();
+        separate.set_merge_window(None);
+        separate.sync_tasks(tasks, clock.at(0));
+        let output = tick(&mut separate, clock.at(600), 0, false).unwrap();
+        assert_eq!(output.triggered[0].task_ids, ["a"]);
+        let output = tick(&mut separate, clock.at(660), 0, false).unwrap();
+        assert_eq!(triggered_ids(&output), ["b"]);
+    }
+
+    #[test]
+    fn escalation_steps_fire_in_order_unless_an_app_holds_the_reminder() {
+        let clock = TestClock::new();
+        let mut a = task("a", 10);
+        a.escalation = vec![
+            EscalationStep { after_seconds: 180, action: EscalationAction::LockScreen },
+            EscalationStep { after_seconds: 60, action: EscalationAction::Renotify },
+            EscalationStep { after_seconds: 120, action: EscalationAction::Sound },
+        ];
+        let mut scheduler = Scheduler::new();
+        scheduler.set_app_rules(vec![AppRule {
+            apps: vec!["zoom".to_string()],
+            task_ids: Vec::new(),
+            action: crate::app_rules::AppRuleAction::NotifyOnly,
+        }]);
+        scheduler.sync_tasks(vec![a], clock.at(0));
+        let zoom = ActiveWindow { class: Some("zoom".to_string()), ..Default::default() };
+
+        let at = |scheduler: &mut Scheduler, seconds, window: Option<ActiveWindow>| {
+            scheduler.tick(clock.at(seconds), &mut FakeIdle(0), &mut FakeInhibit(false), &mut FakeWindow(window), &mut FakeCall(false)).unwrap()
+        };
+        let actions = |output: &TickOutput| output.escalations.iter().map(|e| (e.action, e.step)).collect::<Vec<_>>();
+
+        assert_eq!(triggered_ids(&at(&mut scheduler, 600, None)), ["a"]);
+        assert!(actions(&at(&mut scheduler, 659, None)).is_empty());
+        assert_eq!(actions(&at(&mut scheduler, 660, None)), [(EscalationAction::Renotify, 0)]);
+        // 前台应用要求只通知时暂缓升级
+        assert!(actions(&at(&mut scheduler, 720, Some(zoom.clone()))).is_empty());
+        assert!(actions(&at(&mut scheduler, 790, Some(zoom))).is_empty());
+        // 离开后补上到期的步骤，每个步骤只执行一次
+        assert_eq!(actions(&at(&mut scheduler, 800, None)), [(EscalationAction::Sound, 1), (EscalationAction::LockScreen, 2)]);
+        assert!(actions(&at(&mut scheduler, 900, None)).is_empty());
+        let info = scheduler.countdowns(clock.at(900)).into_iter().next().unwrap();
+        assert_eq!(info.escalations.len(), 3);
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use zbus::blocking::{Connection, MessageIterator, Proxy};
+use zbus::message::Type as MessageType;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue};
+use zbus::MatchRule;
+
+// ============= Linux 会话锁定监听 =============
+//
+// 通过 D-Bus 监听 logind 的 Lock/Unlock 信号与 LockedHint 属性，
+// 以及会话总线上的 org.freedesktop.ScreenSaver.ActiveChanged。
+// 连接由调用方传入，便于在私有 dbus-daemon 上测试。
+
+const LOGIND_SERVICE: &str = "org.freedesktop.login1";
+const LOGIND_SESSION_INTERFACE: &str = "org.freedesktop.login1.Session";
+const SCREENSAVER_SERVICE: &str = "org.freedesktop.ScreenSaver";
+const SCREENSAVER_PATH: &str = "/org/freedesktop/ScreenSaver";
+
+/// 锁定状态的来源
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockSource {
+    Logind,
+    ScreenSaver,
+}
+
+/// 合并多个来源的锁定状态，任一来源锁定即视为锁定
+#[derive(Default)]
+pub struct LockTracker {
+    logind: bool,
+    screensaver: bool,
+    locked: bool,
+}
+
+impl LockTracker {
+    /// 更新某个来源的状态，合并后的状态变化时返回新状态
+    pub fn update(&mut self, source: LockSource, locked: bool) -> Option<bool> {
+        match source {
+            LockSource::Logind => self.logind = locked,
+            LockSource::ScreenSaver => self.screensaver = locked,
+        }
+        let combined = self.logind || self.screensaver;
+        if combined == self.locked {
+            return None;
+        }
+        self.locked = combined;
+        Some(combined)
+    }
+}
+
+/// 查找当前进程所属的 logind 会话
+pub fn find_session_path(conn: &Connection) -> zbus::Result<OwnedObjectPath> {
+    let manager = Proxy::new(
+        conn,
+        LOGIND_SERVICE,
+        "/org/freedesktop/login1",
+        "org.freedesktop.login1.Manager",
+    )?;
+    // 优先使用 XDG_SESSION_ID，否则让 logind 根据调用方自动判断
+    let session_id = std::env::var("XDG_SESSION_ID").unwrap_or_else(|_| "auto".to_string());
+    manager
+        .call("GetSession", &(session_id.as_str(),))
+        .or_else(|_| manager.call("GetSessionByPID", &(std::process::id(),)))
+}
+
+/// 监听 logind 会话的 Lock/Unlock 信号和 LockedHint 属性变化，阻塞直到连接断开
+pub fn watch_logind(
+    conn: &Connection,
+    session_path: &OwnedObjectPath,
+    mut on_change: impl FnMut(bool),
+) -> zbus::Result<()> {
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .sender(LOGIND_SERVICE)?
+        .path(session_path.as_str())?
+        .build();
+    // 先订阅再读取初始状态，避免漏掉中间的变化
+    let messages = MessageIterator::for_match_rule(rule, conn, Some(64))?;
+
+    let session = Proxy::new(conn, LOGIND_SERVICE, session_path.as_str(), LOGIND_SESSION_INTERFACE)?;
+    if let Ok(locked) = session.get_property::<bool>("LockedHint") {
+        on_change(locked);
+    }
+
+    for message in messages {
+        let message = message?;
+        let header = message.header();
+        let (Some(interface), Some(member)) = (header.interface(), header.member()) else {
+            continue;
+        };
+
+        match (interface.as_str(), member.as_str()) {
+            (LOGIND_SESSION_INTERFACE, "Lock") => on_change(true),
+            (LOGIND_SESSION_INTERFACE, "Unlock") => on_change(false),
+            ("org.freedesktop.DBus.Properties", "PropertiesChanged") => {
+                let Ok((iface, changed, _)) = message
+                    .body()
+                    .deserialize::<(String, HashMap<String, OwnedValue>, Vec<String>)>()
+                else {
+                    continue;
+                };
+                if iface != LOGIND_SESSION_INTERFACE {
+                    continue;
+                }
+                if let Some(locked) = changed.get("LockedHint").and_then(|v| v.downcast_ref::<bool>().ok()) {
+                    on_change(locked);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// 监听 org.freedesktop.ScreenSaver.ActiveChanged，阻塞直到连接断开
+pub fn watch_screensaver(conn: &Connection, mut on_change: impl FnMut(bool)) -> zbus::Result<()> {
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .interface(SCREENSAVER_SERVICE)?
+        .member("ActiveChanged")?
+        .build();
+    let messages = MessageIterator::for_match_rule(rule, conn, Some(64))?;
+
+    // 没有屏保服务时忽略初始状态，仍然等待信号
+    if let Ok(screensaver) = Proxy::new(conn, SCREENSAVER_SERVICE, SCREENSAVER_PATH, SCREENSAVER_SERVICE) {
+        if let Ok(active) = screensaver.call::<_, _, bool>("GetActive", &()) {
+            on_change(active);
+        }
+    }
+
+    for message in messages {
+        let message = message?;
+        if let Ok(active) = message.body().deserialize::<bool>() {
+            on_change(active);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use zbus::blocking::connection::Builder;
+
+    const SESSION_PATH: &str = "/org/freedesktop/login1/session/_1";
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// 私有的 dbus-daemon，测试结束时结束进程
+    struct PrivateBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl PrivateBus {
+        fn start() -> Self {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .expect("failed to start dbus-daemon");
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take().unwrap()).read_line(&mut address).unwrap();
+            Self { daemon, address: address.trim().to_string() }
+        }
+
+        fn connect(&self) -> Connection {
+            Builder::address(self.address.as_str()).unwrap().build().unwrap()
+        }
+    }
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    struct FakeSession;
+
+    #[zbus::interface(name = "org.freedesktop.login1.Session")]
+    impl FakeSession {
+        #[zbus(property)]
+        fn locked_hint(&self) -> bool {
+            false
+        }
+    }
+
+    struct FakeScreenSaver;
+
+    #[zbus::interface(name = "org.freedesktop.ScreenSaver")]
+    impl FakeScreenSaver {
+        fn get_active(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    #[ignore = "requires dbus-daemon"]
+    fn logind_signals_and_locked_hint() {
+        let bus = PrivateBus::start();
+        let logind = Builder::address(bus.address.as_str()).unwrap()
+            .name(LOGIND_SERVICE).unwrap()
+            .serve_at(SESSION_PATH, FakeSession).unwrap()
+            .build().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let conn = bus.connect();
+        std::thread::spawn(move || {
+            let path = OwnedObjectPath::try_from(SESSION_PATH).unwrap();
+            let _ = watch_logind(&conn, &path, |locked| tx.send(locked).unwrap());
+        });
+        // 读到初始的 LockedHint 时已完成订阅
+        assert_eq!(rx.recv_timeout(TIMEOUT), Ok(false));
+
+        logind.emit_signal(None::<&str>, SESSION_PATH, LOGIND_SESSION_INTERFACE, "Lock", &()).unwrap();
+        assert_eq!(rx.recv_timeout(TIMEOUT), Ok(true));
+        logind.emit_signal(None::<&str>, SESSION_PATH, LOGIND_SESSION_INTERFACE, "Unlock", &()).unwrap();
+        assert_eq!(rx.recv_timeout(TIMEOUT), Ok(false));
+
+        let changed = HashMap::from([("LockedHint", zbus::zvariant::Value::from(true))]);
+        logind.emit_signal(
+            None::<&str>,
+            SESSION_PATH,
+            "org.freedesktop.DBus.Properties",
+            "PropertiesChanged",
+            &(LOGIND_SESSION_INTERFACE, changed, Vec::<String>::new()),
+        ).unwrap();
+        assert_eq!(rx.recv_timeout(TIMEOUT), Ok(true));
+
+        // 其他程序伪造的信号不处理
+        let other = bus.connect();
+        other.emit_signal(None::<&str>, SESSION_PATH, LOGIND_SESSION_INTERFACE, "Unlock", &()).unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+    }
+
+    #[test]
+    #[ignore = "requires dbus-daemon"]
+    fn screensaver_active_changed() {
+        let bus = PrivateBus::start();
+        let screensaver = Builder::address(bus.address.as_str()).unwrap()
+            .name(SCREENSAVER_SERVICE).unwrap()
+            .serve_at(SCREENSAVER_PATH, FakeScreenSaver).unwrap()
+            .build().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let conn = bus.connect();
+        std::thread::spawn(move || {
+            let _ = watch_screensaver(&conn, |active| tx.send(active).unwrap());
+        });
+        assert_eq!(rx.recv_timeout(TIMEOUT), Ok(false));
+
+        for active in [true, false] {
+            screensaver.emit_signal(None::<&str>, SCREENSAVER_PATH, SCREENSAVER_SERVICE, "ActiveChanged", &(active,)).unwrap();
+            assert_eq!(rx.recv_timeout(TIMEOUT), Ok(active));
+        }
+    }
+
+    #[test]
+    fn tracker_combines_sources() {
+        let mut tracker = LockTracker::default();
+        assert_eq!(tracker.update(LockSource::Logind, true), Some(true));
+        assert_eq!(tracker.update(LockSource::ScreenSaver, true), None);
+        assert_eq!(tracker.update(LockSource::Logind, false), None);
+        assert_eq!(tracker.update(LockSource::ScreenSaver, false), Some(false));
+    }
+}
@@ -0,0 +1,241 @@
+use crate::app_rules::ActiveWindow;
+use crate::scheduler::WindowSource;
+
+// ============= 前台窗口检测 =============
+//
+// 演示、视频通话等全屏程序在前台时，弹出锁屏会打断用户；应用规则按前台应用调整任务。
+// Linux: X11 _NET_ACTIVE_WINDOW 的 _NET_WM_STATE（是否包含 _NET_WM_STATE_FULLSCREEN）、
+//        WM_CLASS 以及 _NET_WM_PID 对应的进程名
+// 其他平台暂不支持，视为没有活动窗口
+
+/// 当前平台的活动窗口检测
+pub struct SystemWindowSource {
+    #[cfg(target_os = "linux")]
+    inner: linux::X11ActiveWindow,
+}
+
+impl SystemWindowSource {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(target_os = "linux")]
+            inner: linux::X11ActiveWindow::new(),
+        }
+    }
+}
+
+impl WindowSource for SystemWindowSource {
+    #[cfg(target_os = "linux")]
+    fn active_window(&mut self) -> Option<ActiveWindow> {
+        self.inner.query()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn active_window(&mut self) -> Option<ActiveWindow> {
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::fs;
+
+    use x11rb::connection::Connection;
+    use x11rb::errors::ReplyError;
+    use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt, GetPropertyReply, Window};
+    use x11rb::rust_connection::RustConnection;
+
+    use crate::app_rules::ActiveWindow;
+
+    x11rb::atom_manager! {
+        Atoms: AtomsCookie {
+            _NET_ACTIVE_WINDOW,
+            _NET_WM_STATE,
+            _NET_WM_STATE_FULLSCREEN,
+            _NET_WM_PID,
+        }
+    }
+
+    /// 定时器线程自己的 X 连接。使用纯 Rust 实现的协议连接而不是 Xlib，
+    /// X 错误作为请求的返回值处理，不需要替换进程全局的 Xlib 错误处理函数（会与 GDK 冲突）
+    struct X11Connection {
+        conn: RustConnection,
+        root: Window,
+        atoms: Atoms,
+    }
+
+    impl X11Connection {
+        fn open(display: Option<&str>) -> Option<Self> {
+            let (conn, screen) = RustConnection::connect(display).ok()?;
+            let root = conn.setup().roots.get(screen)?.root;
+            let atoms = Atoms::new(&conn).ok()?.reply().ok()?;
+            Some(Self { conn, root, atoms })
+        }
+
+        /// 读取窗口属性；属性不存在或类型不符时返回的值为空
+        fn property(&self, window: Window, property: impl Into<Atom>, kind: impl Into<Atom>) -> Result<GetPropertyReply, ReplyError> {
+            self.conn.get_property(false, window, property, kind, 0, 1024)?.reply()
+        }
+
+        /// 32 位格式属性的第一个值
+        fn first_value(&self, window: Window, property: impl Into<Atom>, kind: impl Into<Atom>) -> Result<Option<u32>, ReplyError> {
+            Ok(self.property(window, property, kind)?.value32().and_then(|mut values| values.next()))
+        }
+
+        fn query(&self) -> Result<Option<ActiveWindow>, ReplyError> {
+            let Some(window) = self.first_value(self.root, self.atoms._NET_ACTIVE_WINDOW, AtomEnum::WINDOW)?.filter(|w| *w != 0) else {
+                return Ok(None);
+            };
+            let states: Vec<u32> = self.property(window, self.atoms._NET_WM_STATE, AtomEnum::ATOM)?
+                .value32()
+                .map(|values| values.collect())
+                .unwrap_or_default();
+            // WM_CLASS 为 "实例名\0类名\0"
+            let class = self.property(window, AtomEnum::WM_CLASS, AtomEnum::STRING)?;
+            let mut class = class.value.split(|b| *b == 0)
+                .filter(|part| !part.is_empty())
+                .map(|part| String::from_utf8_lossy(part).into_owned());
+            let process = self.first_value(window, self.atoms._NET_WM_PID, AtomEnum::CARDINAL)?
+                .and_then(|pid| fs::read_to_string(format!("/proc/{}/comm", pid)).ok())
+                .map(|comm| comm.trim().to_string());
+            Ok(Some(ActiveWindow {
+                instance: class.next(),
+                class: class.next(),
+                process,
+                fullscreen: states.contains(&self.atoms._NET_WM_STATE_FULLSCREEN),
+            }))
+        }
+    }
+
+    /// 通过 EWMH 属性读取活动窗口的状态，复用同一个显示连接，断开后下次查询时重新连接
+    pub struct X11ActiveWindow {
+        display: Option<String>,
+        connection: Option<X11Connection>,
+    }
+
+    impl X11ActiveWindow {
+        pub fn new() -> Self {
+            Self::with_display(None)
+        }
+
+        /// 连接指定的显示，None 使用 DISPLAY 环境变量
+        pub fn with_display(display: Option<&str>) -> Self {
+            Self { display: display.map(str::to_string), connection: None }
+        }
+
+        /// 活动窗口的全屏状态、WM_CLASS 和进程名；无法连接、没有活动窗口或窗口刚被关闭时返回 None
+        pub fn query(&mut self) -> Option<ActiveWindow> {
+            if self.connection.is_none() {
+                self.connection = X11Connection::open(self.display.as_deref());
+            }
+            match self.connection.as_ref()?.query() {
+                Ok(window) => window,
+                Err(ReplyError::X11Error(_)) => None,
+                Err(ReplyError::ConnectionError(_)) => {
+                    self.connection = None;
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+pub(crate) mod tests {
+    use std::process::{Child, Command, Stdio};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt, CreateWindowAux, PropMode, WindowClass};
+    use x11rb::rust_connection::RustConnection;
+    use x11rb::wrapper::ConnectionExt as _;
+
+    use super::linux::X11ActiveWindow;
+
+    /// 测试用的 Xvfb 服务器，结束时关闭
+    pub(crate) struct Xvfb {
+        server: Child,
+        pub(crate) display: String,
+    }
+
+    impl Xvfb {
+        pub(crate) fn start() -> Self {
+            static NEXT: AtomicU32 = AtomicU32::new(0);
+            let number = 100 + std::process::id() % 400 * 8 + NEXT.fetch_add(1, Ordering::Relaxed);
+            let display = format!(":{}", number);
+            let server = Command::new("Xvfb")
+                .args([display.as_str(), "-nolisten", "tcp", "-screen", "0", "640x480x24"])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .expect("failed to start Xvfb");
+            let xvfb = Self { server, display };
+            for _ in 0..50 {
+                if RustConnection::connect(Some(&xvfb.display)).is_ok() {
+                    return xvfb;
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            panic!("Xvfb did not start on {}", xvfb.display);
+        }
+    }
+
+    impl Drop for Xvfb {
+        fn drop(&mut self) {
+            let _ = self.server.kill();
+            let _ = self.server.wait();
+        }
+    }
+
+    fn intern(conn: &RustConnection, name: &str) -> u32 {
+        conn.intern_atom(false, name.as_bytes()).unwrap().reply().unwrap().atom
+    }
+
+    #[test]
+    #[ignore = "requires Xvfb"]
+    fn reads_active_window_properties() {
+        let xvfb = Xvfb::start();
+        let (conn, screen) = RustConnection::connect(Some(&xvfb.display)).unwrap();
+        let root = conn.setup().roots[screen].root;
+        let window = conn.generate_id().unwrap();
+        conn.create_window(0, window, root, 0, 0, 100, 100, 0, WindowClass::INPUT_OUTPUT, 0, &CreateWindowAux::new()).unwrap();
+        conn.change_property8(PropMode::REPLACE, window, AtomEnum::WM_CLASS, AtomEnum::STRING, b"zoom\0Zoom\0").unwrap();
+        let fullscreen = intern(&conn, "_NET_WM_STATE_FULLSCREEN");
+        conn.change_property32(PropMode::REPLACE, window, intern(&conn, "_NET_WM_STATE"), AtomEnum::ATOM, &[fullscreen]).unwrap();
+        conn.change_property32(PropMode::REPLACE, window, intern(&conn, "_NET_WM_PID"), AtomEnum::CARDINAL, &[std::process::id()]).unwrap();
+        conn.change_property32(PropMode::REPLACE, root, intern(&conn, "_NET_ACTIVE_WINDOW"), AtomEnum::WINDOW, &[window]).unwrap();
+        conn.sync().unwrap();
+
+        let active = X11ActiveWindow::with_display(Some(&xvfb.display)).query().unwrap();
+        assert_eq!(active.instance.as_deref(), Some("zoom"));
+        assert_eq!(active.class.as_deref(), Some("Zoom"));
+        assert!(active.fullscreen);
+        let comm = std::fs::read_to_string("/proc/self/comm").unwrap();
+        assert_eq!(active.process.as_deref(), Some(comm.trim()));
+    }
+
+    #[test]
+    #[ignore = "requires Xvfb"]
+    fn closed_active_window_is_not_an_error() {
+        let xvfb = Xvfb::start();
+        let (conn, screen) = RustConnection::connect(Some(&xvfb.display)).unwrap();
+        let root = conn.setup().roots[screen].root;
+        let active_atom = intern(&conn, "_NET_ACTIVE_WINDOW");
+        let mut source = X11ActiveWindow::with_display(Some(&xvfb.display));
+        assert!(source.query().is_none());
+
+        // 活动窗口已不存在时，查询失败但连接仍然可用
+        let closed = conn.generate_id().unwrap();
+        conn.change_property32(PropMode::REPLACE, root, active_atom, AtomEnum::WINDOW, &[closed]).unwrap();
+        conn.sync().unwrap();
+        assert!(source.query().is_none());
+
+        let window = conn.generate_id().unwrap();
+        conn.create_window(0, window, root, 0, 0, 100, 100, 0, WindowClass::INPUT_OUTPUT, 0, &CreateWindowAux::new()).unwrap();
+        conn.change_property32(PropMode::REPLACE, root, active_atom, AtomEnum::WINDOW, &[window]).unwrap();
+        conn.sync().unwrap();
+        let active = source.query().unwrap();
+        assert!(!active.fullscreen);
+        assert_eq!(active.class, None);
+    }
+}
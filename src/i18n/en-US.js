@@ -28,6 +28,10 @@ export default {
       title: 'New Reminder',
       desc: 'Another energetic day, remember to take breaks~',
     },
+    pomodoro: {
+      title: 'Pomodoro',
+      desc: 'Pomodoro done, step away and take a break~',
+    },
   },
 
   // Statistics
@@ -51,12 +55,15 @@ export default {
     resetAll: 'Reset All',
     gotIt: 'Got It',
     snooze: 'Snooze {minutes} min',
+    addAppRule: 'Add Rule',
     addTask: 'Add Custom Reminder',
+    addPomodoro: 'Add Pomodoro',
     checkUpdate: 'Check Update',
     updateNow: 'Update Now',
     checking: 'Checking...',
     test: 'Test',
     confirmRest: 'Rest Completed',
+    save: 'Save',
   },
 
   // Lock screen
@@ -67,8 +74,18 @@ export default {
     timeUp: 'Rest time is up!',
     confirmMessage: 'Have you finished your rest? Click the button to confirm~',
     snoozeLimit: 'Snooze limit reached',
+    snoozeCooldown: 'Snooze is cooling down',
     strictDisabled: 'Snooze disabled in strict mode',
     snoozeDuring: 'Snoozed {time}',
+    inputGrabFailed: 'Could not lock keyboard and mouse, other windows may still be reachable',
+    emergencyOpen: 'Emergency unlock',
+    emergencyChallenge: 'Type the following sentence to unlock:',
+    emergencyPlaceholder_pin: 'Enter PIN',
+    emergencyPlaceholder_passphrase: 'Enter passphrase',
+    emergencyPlaceholder_challenge: 'Type the sentence above',
+    emergencySubmit: 'Unlock',
+    emergencyWrong: 'Incorrect, {left} attempts left',
+    emergencyTooMany: 'Too many failed attempts, emergency unlock is disabled for this break',
   },
 
   // Settings
@@ -77,7 +94,7 @@ export default {
     lockScreen: 'Force Rest Lock Screen',
     lockScreenDesc: 'Lock screen when reminder triggers to ensure real rest',
     strictMode: 'Strict Mode',
-    strictModeDesc: 'Hides the emergency unlock button on lock screen, use with caution',
+    strictModeDesc: 'Hides the hold-to-unlock button on lock screen, only emergency unlock can end a break early, use with caution',
     advanced: 'Advanced Settings',
     autoUnlock: 'Auto Unlock After Countdown',
     autoUnlockDesc: 'Automatically exit lock screen when rest ends, no confirmation needed',
@@ -85,13 +102,46 @@ export default {
     resetOnIdleDesc: 'Automatically reset timers when user is away from computer',
     allowStrictSnooze: 'Allow Snooze in Strict Mode',
     allowStrictSnoozeDesc: 'When enabled, snooze is allowed even in strict mode',
+    emergencyUnlock: 'Emergency Unlock',
+    emergencyUnlockDesc: 'Unlock a strict lock screen with a PIN, passphrase or typed sentence; every use is logged',
+    emergencyUnlockLast: 'Last emergency unlock: {time}',
+    emergencyUnlock_off: 'Off',
+    emergencyUnlock_pin: 'PIN',
+    emergencyUnlock_passphrase: 'Passphrase',
+    emergencyUnlock_challenge: 'Type a sentence',
+    emergencyPinPlaceholder: '4 to 12 digits',
+    emergencyPassphrasePlaceholder: 'At least 8 characters',
     enableMerge: 'Merge Tasks',
     enableMergeDesc: 'Proactively merge upcoming tasks to rest together when a task triggers',
     mergeThreshold: 'Merge Threshold',
     mergeThresholdDesc: 'Tasks with remaining time less than this will be merged',
+    quietHours: 'Quiet Hours',
+    quietHoursDesc: 'No reminders or lock screens during these periods, e.g. 19:00-09:00, 12:00-13:00',
+    quietPolicy: 'Reminders Due in Quiet Hours',
+    quietPolicy_drop: 'Skip',
+    quietPolicy_defer: 'Show afterwards',
+    quietPolicy_collapse: 'Show once afterwards',
+    fullscreenPolicy: 'Fullscreen Apps',
+    fullscreenPolicyDesc: 'How reminders behave while a presentation or video is fullscreen; 0 minutes means no limit',
+    fullscreenPolicy_ignore: 'Remind as usual',
+    fullscreenPolicy_defer: 'Defer',
+    fullscreenPolicy_notify_only: 'Notification only',
+    callDetection: 'Defer During Calls',
+    callDetectionDesc: 'While the camera or microphone is in use, reminders wait until the call ends, for at most the given minutes (0 for no limit)',
+    appRules: 'App Rules',
+    appRulesDesc: 'Adjust reminders by the focused app (WM_CLASS or process name, comma separated)',
+    appRuleAllTasks: 'All tasks',
+    appRuleAction_defer: 'Defer while focused',
+    appRuleAction_notify_only: 'Never lock while focused',
+    appRuleAction_only_while: 'Count only while focused',
+    appRuleAction_trigger_after: 'Remind after continuous use',
     idleThreshold: 'Idle Detection Threshold',
     idleThresholdDesc: 'Considered idle after this duration of inactivity',
     idleThresholdDescIdle: 'Considered idle after this duration of inactivity (Currently Idle)',
+    idleBackend: 'Idle detection: {backend}',
+    idleBackendUnavailable: 'Idle detection unavailable, reset on idle will not work',
+    offlineBreakThreshold: 'Offline Break Threshold',
+    offlineBreakThresholdDesc: 'If the app was closed longer than this, timers restart as after a break',
     maxSnoozeCount: 'Max Snooze Count',
     maxSnoozeCountDesc: 'Maximum consecutive snoozes allowed after task triggers',
     sound: 'Notification Sound',
@@ -110,15 +160,51 @@ export default {
     clickToReset: 'Click to reset',
     settings: 'Settings',
     resetTask: 'Reset this task',
+    nextAt: 'next at {time}',
+    breakAfterIdle: 'Break after idle',
+    inhibitPolicy: 'During video',
+    inhibit_passive: 'Pause',
+    inhibit_idle: 'Pause without input',
+    inhibit_active: 'Working',
+    countMode: 'Count',
+    escalation: 'If ignored',
+    escalation_off: 'Do nothing',
+    escalation_gentle: 'Remind again',
+    escalation_strict: 'Then lock',
+    countMode_wall_clock: 'Clock time',
+    countMode_active_time: 'Active time',
+    satisfies: 'Also counts for',
+    pomodoroBreaks: 'Short / long break',
+    pomodoroLongEvery: 'Long break every',
+  },
+
+  // Pomodoro
+  pomodoro: {
+    phase_work: 'Focus',
+    phase_short_break: 'Short break',
+    phase_long_break: 'Long break',
+    progress: '{phase} {cycle}/{cycles} · {completed} today',
   },
 
   // Status
   status: {
     paused: 'Paused',
+    pausedFor: 'Paused · {time} left',
+    quietUntil: 'Quiet hours · until {time}',
+    deferred_fullscreen: 'Deferred · fullscreen app',
+    deferred_call: 'Deferred · break after your call',
+    deferred_app: 'Deferred · {app} in use',
+    appPaused: 'paused · app not focused',
     idle: 'Idle',
     loading: 'Loading...',
     noActiveTask: 'No Active Task',
     snoozed: 'Snoozed',
+    outsideHours: 'Outside hours',
+    outsideHoursUntil: 'Outside hours · resumes {time}',
+    mediaPaused: 'paused during video',
+    waitingInput: 'waiting for input',
+    unanswered: 'unanswered for {minutes} min',
+    coveredBy: 'covered by {task}',
   },
 
   // Notifications
@@ -28,6 +28,10 @@ export default {
       title: '新提醒',
       desc: '又是充满活力的一天，记得休息哦~',
     },
+    pomodoro: {
+      title: '番茄钟',
+      desc: '完成一个番茄钟，离开屏幕休息一下吧~',
+    },
   },
 
   // 统计
@@ -51,12 +55,15 @@ export default {
     resetAll: '全部重置',
     gotIt: '我知道了',
     snooze: '推迟 {minutes} 分钟',
+    addAppRule: '添加规则',
     addTask: '添加自定义提醒',
+    addPomodoro: '添加番茄钟',
     checkUpdate: '检查更新',
     updateNow: '立即更新',
     checking: '检查中...',
     test: '测试',
     confirmRest: '已完成休息',
+    save: '保存',
   },
 
   // 锁屏
@@ -67,8 +74,18 @@ export default {
     timeUp: '休息时间到！',
     confirmMessage: '您完成休息了吗？点击下方按钮确认~',
     snoozeLimit: '已达推迟上限',
+    snoozeCooldown: '推迟冷却中，请稍后再试',
     strictDisabled: '严格模式已禁用推迟',
     snoozeDuring: '推迟中 {time}',
+    inputGrabFailed: '未能锁定键盘和鼠标，仍可能切换到其他窗口',
+    emergencyOpen: '紧急解锁',
+    emergencyChallenge: '输入下面的句子以解锁：',
+    emergencyPlaceholder_pin: '输入 PIN',
+    emergencyPlaceholder_passphrase: '输入口令',
+    emergencyPlaceholder_challenge: '逐字输入上面的句子',
+    emergencySubmit: '解锁',
+    emergencyWrong: '输入错误，还可尝试 {left} 次',
+    emergencyTooMany: '失败次数过多，本次休息不能再紧急解锁',
   },
 
   // 设置
@@ -77,7 +94,7 @@ export default {
     lockScreen: '强制休息锁屏',
     lockScreenDesc: '提醒时锁定屏幕，确保真正休息',
     strictMode: '严格模式',
-    strictModeDesc: '开启后锁屏界面将隐藏"紧急解锁"按钮，请谨慎开启',
+    strictModeDesc: '开启后锁屏界面将隐藏长按解锁按钮，只能通过紧急解锁提前结束休息，请谨慎开启',
     advanced: '高级设置',
     autoUnlock: '倒计时结束自动解锁',
     autoUnlockDesc: '休息结束后自动退出锁屏，无需手动确认',
@@ -85,13 +102,46 @@ export default {
     resetOnIdleDesc: '当用户离开电脑（空闲）时自动重置计时',
     allowStrictSnooze: '严格模式允许推迟',
     allowStrictSnoozeDesc: '开启后，即使在严格模式下也允许使用推迟功能',
+    emergencyUnlock: '紧急解锁',
+    emergencyUnlockDesc: '严格模式下可输入 PIN、口令或随机句子解锁，每次使用都会记录',
+    emergencyUnlockLast: '上次紧急解锁：{time}',
+    emergencyUnlock_off: '关闭',
+    emergencyUnlock_pin: 'PIN',
+    emergencyUnlock_passphrase: '口令',
+    emergencyUnlock_challenge: '输入随机句子',
+    emergencyPinPlaceholder: '4 到 12 位数字',
+    emergencyPassphrasePlaceholder: '至少 8 个字符',
     enableMerge: '合并任务',
     enableMergeDesc: '当一个任务触发时，将临近的任务提前合并一起休息',
     mergeThreshold: '合并阈值',
     mergeThresholdDesc: '剩余时间小于此值的任务将被合并',
+    quietHours: '免打扰时段',
+    quietHoursDesc: '期间不弹出提醒和锁屏，例如 19:00-09:00, 12:00-13:00',
+    quietPolicy: '免打扰期间到期的提醒',
+    quietPolicy_drop: '跳过',
+    quietPolicy_defer: '结束后提醒',
+    quietPolicy_collapse: '结束后合并提醒',
+    fullscreenPolicy: '全屏程序',
+    fullscreenPolicyDesc: '演示或视频全屏时如何提醒，推迟 0 分钟表示不限时长',
+    fullscreenPolicy_ignore: '照常提醒',
+    fullscreenPolicy_defer: '推迟提醒',
+    fullscreenPolicy_notify_only: '只发通知',
+    callDetection: '通话时推迟',
+    callDetectionDesc: '摄像头或麦克风被占用时，到期的提醒推迟到通话结束，最长推迟设定的分钟数（0 为不限）',
+    appRules: '应用规则',
+    appRulesDesc: '按前台应用调整提醒（WM_CLASS 或进程名，逗号分隔）',
+    appRuleAllTasks: '全部任务',
+    appRuleAction_defer: '在前台时推迟提醒',
+    appRuleAction_notify_only: '在前台时不锁屏',
+    appRuleAction_only_while: '只在前台时计时',
+    appRuleAction_trigger_after: '连续使用后提醒',
     idleThreshold: '空闲检测阈值',
     idleThresholdDesc: '超过此时间无操作视为空闲',
     idleThresholdDescIdle: '超过此时间无操作视为空闲 (当前空闲中)',
+    idleBackend: '空闲检测方式：{backend}',
+    idleBackendUnavailable: '空闲检测不可用，空闲重置不会生效',
+    offlineBreakThreshold: '离线休息阈值',
+    offlineBreakThresholdDesc: '程序关闭超过该时长时，视为已休息并重新计时',
     maxSnoozeCount: '最大推迟次数',
     maxSnoozeCountDesc: '任务触发后允许连续推迟的次数',
     sound: '提示音',
@@ -110,15 +160,51 @@ export default {
     clickToReset: '点击重置',
     settings: '设置',
     resetTask: '重置此任务',
+    nextAt: '下次 {time}',
+    breakAfterIdle: '空闲视为休息',
+    inhibitPolicy: '播放视频时',
+    inhibit_passive: '暂停计时',
+    inhibit_idle: '无输入时暂停',
+    inhibit_active: '视为工作',
+    countMode: '计时方式',
+    escalation: '未处理时',
+    escalation_off: '不再提醒',
+    escalation_gentle: '再次提醒',
+    escalation_strict: '最终锁屏',
+    countMode_wall_clock: '按时钟',
+    countMode_active_time: '按操作时间',
+    satisfies: '同时算作',
+    pomodoroBreaks: '短休息 / 长休息',
+    pomodoroLongEvery: '长休息间隔（个）',
+  },
+
+  // 番茄钟
+  pomodoro: {
+    phase_work: '专注',
+    phase_short_break: '短休息',
+    phase_long_break: '长休息',
+    progress: '{phase} {cycle}/{cycles} · 今天 {completed} 个',
   },
 
   // 状态
   status: {
     paused: '已暂停',
+    pausedFor: '已暂停 · 剩余 {time}',
+    quietUntil: '免打扰中 · {time} 结束',
+    deferred_fullscreen: '已推迟 · 全屏程序运行中',
+    deferred_call: '已推迟 · 通话结束后休息',
+    deferred_app: '已推迟 · 正在使用 {app}',
+    appPaused: '前台应用不符，暂停计时',
     idle: '空闲中',
     loading: '正在加载...',
     noActiveTask: '无活动任务',
     snoozed: '推迟中',
+    outsideHours: '非活动时段',
+    outsideHoursUntil: '非活动时段 · {time} 恢复',
+    mediaPaused: '播放视频中暂停',
+    waitingInput: '等待操作',
+    unanswered: '已 {minutes} 分钟未处理',
+    coveredBy: '随{task}一起完成',
   },
 
   // 通知
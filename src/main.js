@@ -22,11 +22,25 @@ const ICONS = {
 };
 
 const DEFAULT_TASKS = [
-  { id: 'sit', title: '久坐提醒', desc: '该起来活动了，走动一下吧~', interval: 45, enabled: true, icon: 'sit', lockDuration: 60, autoResetOnIdle: true, preNotificationSeconds: 5, snoozeMinutes: 5 },
-  { id: 'water', title: '喝水提醒', desc: '该喝口水了，保持水分充足~', interval: 60, enabled: true, icon: 'water', lockDuration: 60, autoResetOnIdle: true, preNotificationSeconds: 5, snoozeMinutes: 5 },
-  { id: 'eye', title: '护眼提醒', desc: '让眼睛休息一下，看看远处~', interval: 20, enabled: true, icon: 'eye', lockDuration: 60, autoResetOnIdle: true, preNotificationSeconds: 5, snoozeMinutes: 2 }
+  { id: 'sit', title: '久坐提醒', desc: '该起来活动了，走动一下吧~', interval: 45, enabled: true, icon: 'sit', lockDuration: 60, autoResetOnIdle: true, preNotificationSeconds: 5, snoozeMinutes: 5, inhibitPolicy: 'active', idleThreshold: 300, satisfies: ['eye'] },
+  { id: 'water', title: '喝水提醒', desc: '该喝口水了，保持水分充足~', interval: 60, enabled: true, icon: 'water', lockDuration: 60, autoResetOnIdle: true, preNotificationSeconds: 5, snoozeMinutes: 5, inhibitPolicy: 'passive' },
+  { id: 'eye', title: '护眼提醒', desc: '让眼睛休息一下，看看远处~', interval: 20, enabled: true, icon: 'eye', lockDuration: 60, autoResetOnIdle: true, preNotificationSeconds: 5, snoozeMinutes: 2, inhibitPolicy: 'active', idleThreshold: 20 }
 ];
 
+// 提醒未处理时的升级方案：after_seconds 秒后执行 action
+const ESCALATION_PRESETS = {
+  off: [],
+  gentle: [
+    { after_seconds: 120, action: 'renotify' },
+    { after_seconds: 300, action: 'sound' },
+  ],
+  strict: [
+    { after_seconds: 120, action: 'renotify' },
+    { after_seconds: 300, action: 'sound' },
+    { after_seconds: 600, action: 'lock_screen' },
+  ],
+};
+
 let settings = {
   tasks: [...DEFAULT_TASKS],
   soundEnabled: true,
@@ -34,6 +48,7 @@ let settings = {
   lockScreenEnabled: false,
   lockDuration: 20,
   idleThreshold: 300,  // 空闲阈值，秒，默认 5 分钟
+  offlineBreakThreshold: 300, // 程序离线超过该时长视为已休息，秒
   autoUnlock: true,    // 倒计时结束自动解锁
   strictMode: false,   // 严格模式：隐藏紧急解锁按钮
   snoozeMinutes: 5,    // 推迟时间（分钟）
@@ -43,10 +58,24 @@ let settings = {
   allowStrictSnooze: false, // 严格模式下是否允许推迟
   enableMerge: true,  // 是否合并临近任务
   mergeThreshold: 60,  // 合并阈值（秒）
+  quietHoursEnabled: false, // 是否启用免打扰时段
+  quietHours: '19:00-09:00, 12:00-13:00', // 免打扰时段，逗号分隔的 HH:MM-HH:MM
+  quietPolicy: 'defer', // 免打扰期间到期的提醒：drop / defer / collapse
+  fullscreenPolicy: 'defer', // 前台全屏时到期的提醒：ignore / defer / notify_only
+  fullscreenMaxDefer: 30, // 全屏时最长推迟分钟数，0 为不限
+  callDetection: true, // 通话（摄像头或麦克风被占用）时推迟提醒
+  callMaxDefer: 60, // 通话时最长推迟分钟数，0 为推迟到通话结束
+  appRules: [], // 前台应用规则：{ apps, action, taskId, minutes }
   language: 'zh-CN',   // 界面语言
 };
 
 let countdowns = {};  // 现在由后端事件更新
+let countdownTotals = {}; // 每个任务本轮的总时长（秒）
+let nextDueAt = {}; // 固定时刻任务的下次触发时间（毫秒时间戳）
+let outsideHours = {}; // 活动时段之外的任务：{ resumesAt }
+let passiveTasks = {}; // 屏保被抑制而冻结的任务
+let inactiveTasks = {}; // 按有效时间计时且当前没有输入的任务
+let unansweredSince = {}; // 已触发但尚未处理的提醒：触发时间（毫秒时间戳）
 let snoozedStatus = {}; // 推迟状态
 let stats = {
   sitBreaks: 0,
@@ -54,18 +83,30 @@ let stats = {
   workMinutes: 0,
 };
 let isPaused = false;
+let pauseRemaining = null; // 定时暂停距自动恢复的秒数，手动暂停时为 null
+let quietUntil = null; // 处于免打扰时段时的结束时间（毫秒时间戳）
+let deferredTasks = {}; // 到期但被推迟的任务：推迟原因及相关应用
+let appPausedTasks = {}; // 前台不是规则要求的应用而暂停计时的任务
+let pomodoroStatus = {}; // 番茄钟任务的阶段和进度
+let coveredBy = {}; // 等待其他任务的提醒完成后一并重置的任务
 let isIdle = false;  // 当前是否处于空闲状态
+let idleBackend = 'none'; // 后端正在使用的空闲检测方式，'none' 表示不可用
 let workStartTime = Date.now();
 let activePopup = null;
 let taskQueue = []; // 任务队列
 let lockScreenState = {
   active: false,
   remaining: 0,
+  duration: 0,
   task: null,
   unlockProgress: 0,
   unlockTimer: null,
   waitingConfirm: false,
+  strictMode: false,
 };
+let isLockSlave = false; // 其他屏幕上的锁屏窗口
+// 紧急解锁设置保存在后端，这里只记录方式、最近一次解锁和正在设置的方式
+let emergencyUnlock = { method: null, last: null, pending: null, error: null };
 
 let updateInfo = null;
 let isUpdating = false;
@@ -87,7 +128,20 @@ async function syncTasksToBackend() {
     interval: t.interval,
     enabled: t.enabled,
     icon: t.icon,
-    auto_reset_on_idle: settings.resetOnIdle // 使用全局设置
+    auto_reset_on_idle: t.autoResetOnIdle ?? settings.resetOnIdle, // 任务未单独设置时使用全局设置
+    schedule: t.schedule, // 固定时刻日程，缺省为按间隔循环
+    active_hours: t.activeHours, // 活动时段，缺省为全天
+    inhibit_policy: t.inhibitPolicy || 'passive', // 播放视频等抑制屏保时的计时方式
+    count_mode: t.countMode || 'wall_clock', // 按墙上时间或只按有输入的时间计时
+    idle_threshold: t.idleThreshold ?? null, // 空闲多少秒视为已休息，缺省使用全局阈值
+    escalation: ESCALATION_PRESETS[t.escalation] || [], // 提醒未处理时的升级步骤
+    snooze: {
+      max_count: parseInt(t.maxSnoozeCount ?? settings.maxSnoozeCount),
+      durations: [parseInt(t.snoozeMinutes || 5)],
+      cooldown_seconds: parseInt(t.snoozeCooldownSeconds || 0),
+      allow_in_strict: !!(t.allowStrictSnooze ?? settings.allowStrictSnooze)
+    },
+    satisfies: t.satisfies || [] // 完成本任务的休息时一并视为完成休息的任务
   }));
   await invoke('sync_tasks', { tasks: tasksForBackend }).catch(console.error);
 }
@@ -95,51 +149,11 @@ async function syncTasksToBackend() {
 async function init() {
   const urlParams = new URLSearchParams(window.location.search);
   if (urlParams.get('mode') === 'lock_slave') {
-    const task = {
-      title: urlParams.get('title') || '休息时间',
-      desc: urlParams.get('desc') || '让眼睛休息一下',
-      icon: urlParams.get('icon') || 'eye',
-      id: 'slave_lock'
-    };
-    const duration = parseInt(urlParams.get('duration') || '10');
-
-    // Parse slave settings
-    settings.lockDuration = duration;
-    settings.strictMode = urlParams.get('strict_mode') === 'true';
-    settings.allowStrictSnooze = urlParams.get('allow_strict_snooze') === 'true';
-    settings.maxSnoozeCount = parseInt(urlParams.get('max_snooze_count') || '1');
-    
-    const taskSnoozeMinutes = parseInt(urlParams.get('snooze_minutes') || '5');
-    const currentSnoozeCount = parseInt(urlParams.get('current_snooze_count') || '0');
-    
-    task.snoozeMinutes = taskSnoozeMinutes;
-    snoozedStatus[task.id] = { count: currentSnoozeCount, active: false, remaining: 0 };
-
-    lockScreenState = {
-      active: true,
-      remaining: duration,
-      task: task,
-      unlockProgress: 0,
-      unlockTimer: null,
-      waitingConfirm: false,
-    };
-
-    renderFullUI();
-
-    // 隐藏从属屏幕的解锁按钮
-    setTimeout(() => {
-      const btn = document.querySelector('.unlock-btn');
-      if (btn) btn.style.display = 'none';
-    }, 0);
-
-    const lockInterval = setInterval(() => {
-      lockScreenState.remaining--;
-      updateLockScreenTimer();
-      if (lockScreenState.remaining <= 0) {
-        clearInterval(lockInterval);
-      }
-    }, 1000);
-
+    // 从属屏幕只显示后端锁屏会话的状态，推迟和解锁等操作同样交给会话，对所有屏幕生效
+    isLockSlave = true;
+    listen('lock-tick', (event) => applyLockTick(event.payload));
+    const tick = await invoke('get_lock_session').catch(console.error);
+    if (tick) applyLockTick(tick);
     return;
   }
 
@@ -175,9 +189,21 @@ async function init() {
     }
   });
 
+  // 离线休息阈值需在首次同步任务前设置，后端会据此恢复上次的计时进度
+  await invoke('set_offline_break_threshold', { seconds: settings.offlineBreakThreshold }).catch(console.error);
+
   // 同步任务到后端定时器
   await syncTasksToBackend();
 
+  // 同步提醒合并窗口、免打扰时段和严格模式到后端
+  syncMergeWindow();
+  syncQuietHours();
+  syncFullscreenPolicy();
+  syncCallDetection();
+  syncAppRules();
+  invoke('timer_set_strict_mode', { enabled: !!settings.strictMode }).catch(console.error);
+  await loadEmergencyUnlock();
+
   // 同步空闲阈值到后端
   await invoke('set_idle_threshold', { seconds: settings.idleThreshold }).catch(console.error);
 
@@ -196,19 +222,35 @@ async function init() {
   // 监听后端倒计时更新事件
   listen('countdown-update', (event) => {
     const updates = event.payload;
+    pauseRemaining = updates.length > 0 ? updates[0].pause_remaining : null;
+    quietUntil = updates.length > 0 ? updates[0].quiet_until : null;
     updates.forEach(info => {
       countdowns[info.id] = info.remaining;
+      countdownTotals[info.id] = info.total;
+      nextDueAt[info.id] = info.next_due_at;
+      outsideHours[info.id] = info.outside_hours ? { resumesAt: info.resumes_at } : null;
+      passiveTasks[info.id] = info.passive;
+      inactiveTasks[info.id] = info.inactive;
+      appPausedTasks[info.id] = info.app_paused;
+      pomodoroStatus[info.id] = info.pomodoro;
+      coveredBy[info.id] = info.covered_by;
+      unansweredSince[info.id] = info.triggered_at;
+      if (info.remaining > 0) {
+        delete deferredTasks[info.id];
+      }
       snoozedStatus[info.id] = { 
         active: info.snoozed, 
         remaining: info.snooze_remaining,
-        count: info.snooze_count
+        count: info.snooze_count,
+        options: info.snooze_options,
+        blocked: info.snooze_blocked
       };
       
       // 预提醒逻辑
       const task = settings.tasks.find(t => t.id === info.id);
       const preNotifyTime = (task && task.preNotificationSeconds !== undefined) ? task.preNotificationSeconds : 5;
       
-      if (info.enabled && !isIdle && !isPaused && preNotifyTime > 0 && info.remaining === preNotifyTime) {
+      if (info.enabled && !info.outside_hours && !info.passive && !isIdle && !isPaused && !quietUntil && preNotifyTime > 0 && info.remaining === preNotifyTime) {
         if (task) {
            if (settings.soundEnabled) {
              invoke('play_notification_sound').catch(() => {});
@@ -230,8 +272,13 @@ async function init() {
   // 监听后端任务触发事件
   listen('task-triggered', async (event) => {
     const task = event.payload;
-    // 找到完整的任务配置
-    const fullTask = settings.tasks.find(t => t.id === task.id) || task;
+    // 找到完整的任务配置，合并的任务由后端决定
+    const fullTask = { ...(settings.tasks.find(t => t.id === task.id) || task), mergedTaskIds: task.task_ids, notifyOnly: task.notify_only };
+    task.task_ids.forEach(id => delete deferredTasks[id]);
+    // 番茄钟按当前阶段决定休息时长
+    if (task.lock_duration) {
+      fullTask.lockDuration = task.lock_duration;
+    }
     
     if (activePopup || lockScreenState.active) {
       // 如果当前已有弹窗或锁屏，加入队列
@@ -254,6 +301,100 @@ async function init() {
     }
   });
 
+  // 监听空闲检测后端变化
+  listen('idle-backend-changed', (event) => {
+    idleBackend = event.payload;
+    const el = document.getElementById('idleBackendDesc');
+    if (el) el.textContent = getIdleBackendText();
+  });
+  invoke('get_idle_backend').then((backend) => {
+    idleBackend = backend;
+    const el = document.getElementById('idleBackendDesc');
+    if (el) el.textContent = getIdleBackendText();
+  }).catch(console.error);
+
+  // 提醒长时间未处理，后端已按升级策略通知或播放提示音，强制锁屏在此处理
+  listen('task-escalated', async (event) => {
+    const { id, task_ids, action, lock_duration } = event.payload;
+    if (action !== 'lock_screen' || lockScreenState.active) return;
+    const task = settings.tasks.find(t => t.id === id);
+    if (!task) return;
+    // 番茄钟按提醒时的阶段决定休息时长，与直接锁屏相同
+    const mergedTasks = task_ids.map(tid => settings.tasks.find(t => t.id === tid)).filter(Boolean);
+    taskQueue = taskQueue.filter(t => !task_ids.includes(t.id));
+    activePopup = null;
+    await startLockScreen(lock_duration ? { ...task, lockDuration: lock_duration } : task, mergedTasks);
+  });
+
+  // 锁屏倒计时由后端的锁屏会话推进
+  listen('lock-tick', (event) => applyLockTick(event.payload));
+
+  // 主循环卡住时强制释放输入抓取失败
+  listen('input-grab-error', (event) => console.error('Failed to release the input grab', event.payload));
+
+  // 锁屏会话结束：完成休息的任务已由后端重置，推迟的任务已由后端推迟
+  listen('lock-ended', (event) => {
+    const { reason, task_ids, reset_ids } = event.payload;
+    cancelUnlockPress();
+    lockScreenState.active = false;
+    lockScreenState.waitingConfirm = false;
+    lockScreenState.emergencyOpen = false;
+    lockScreenState.emergencyRejected = false;
+    lockScreenState.emergencyChallenge = null;
+
+    // 从队列中移除本次锁屏包含和抵扣的任务，防止解锁后再次弹窗
+    taskQueue = taskQueue.filter(t => !task_ids.includes(t.id) && !reset_ids.includes(t.id));
+    if (reason === 'emergency') {
+      loadEmergencyUnlock();
+    }
+    // 提前解锁和紧急解锁不算完成休息
+    if (reason === 'completed') {
+      task_ids.forEach(id => {
+        if (id === 'sit') stats.sitBreaks++;
+        if (id === 'water') stats.waterCups++;
+      });
+      saveStats();
+    }
+    settings.tasks.filter(t => reset_ids.includes(t.id)).forEach(clearTaskCountdown);
+    updateTrayTooltip(true);
+    processNextTask();
+  });
+
+  // 网页刷新时锁屏会话仍在进行，恢复锁屏界面
+  invoke('get_lock_session').then((tick) => {
+    if (tick) applyLockTick(tick);
+  }).catch(console.error);
+
+  // 到期提醒因前台全屏等原因被后端推迟
+  listen('reminder-deferred', (event) => {
+    const { task_ids, reason, app } = event.payload;
+    task_ids.forEach(id => {
+      deferredTasks[id] = { reason, app };
+    });
+    if (!isUiSuspended) {
+      updateLiveValues();
+    }
+  });
+
+  // 用户自然离开达到任务的休息阈值，后端已重置这些任务
+  listen('natural-break', (event) => {
+    const ids = event.payload.task_ids;
+    taskQueue = taskQueue.filter(t => !ids.includes(t.id));
+    ids.forEach(id => {
+      if (snoozedStatus[id]) {
+        snoozedStatus[id].active = false;
+      }
+    });
+    ids.forEach(id => {
+      if (id === 'sit') stats.sitBreaks++;
+      if (id === 'water') stats.waterCups++;
+    });
+    saveStats();
+    if (!isUiSuspended) {
+      updateLiveValues();
+    }
+  });
+
   listen('show-window', () => {
     invoke('show_main_window');
   });
@@ -266,6 +407,16 @@ async function init() {
     togglePause();
   });
 
+  // 托盘「暂停一段时间」或 timer_pause_for 开始定时暂停
+  listen('timer-paused', () => {
+    setPaused(true);
+  });
+
+  // 定时暂停到期，后端已自动恢复计时
+  listen('timer-auto-resumed', () => {
+    setPaused(false);
+  });
+
   listen('system-locked', () => {
     invoke('timer_set_system_locked', { locked: true }).catch(console.error);
   });
@@ -356,6 +507,8 @@ async function loadSettings() {
         return {
           preNotificationSeconds: def ? def.preNotificationSeconds : 5,
           snoozeMinutes: def ? def.snoozeMinutes : 5,
+          inhibitPolicy: def ? def.inhibitPolicy : 'passive',
+          idleThreshold: def ? def.idleThreshold : undefined,
           ...task
         };
       });
@@ -373,6 +526,75 @@ async function loadSettings() {
   }
 }
 
+function syncMergeWindow() {
+  const seconds = settings.enableMerge ? settings.mergeThreshold : 0;
+  invoke('set_merge_window', { seconds }).catch(console.error);
+}
+
+// 解析 "19:00-09:00, 12:00-13:00" 形式的时段列表，忽略格式错误的项
+function parseTimeRanges(text) {
+  return (text || '').split(',')
+    .map(range => range.trim().match(/^(\d{1,2}:\d{2})\s*-\s*(\d{1,2}:\d{2})$/))
+    .filter(Boolean)
+    .map(([, start, end]) => ({ start, end }));
+}
+
+function syncQuietHours() {
+  const windows = parseTimeRanges(settings.quietHours);
+  const quietHours = settings.quietHoursEnabled && windows.length > 0 ? { windows } : null;
+  invoke('set_quiet_hours', { quietHours, policy: settings.quietPolicy || 'defer' }).catch(console.error);
+}
+
+function syncFullscreenPolicy() {
+  invoke('set_fullscreen_policy', {
+    policy: settings.fullscreenPolicy || 'defer',
+    maxDeferSeconds: (settings.fullscreenMaxDefer ?? 30) * 60
+  }).catch(console.error);
+}
+
+async function loadEmergencyUnlock() {
+  try {
+    emergencyUnlock.method = await invoke('get_emergency_unlock');
+    emergencyUnlock.last = (await invoke('get_emergency_unlock_log', { limit: 1 }))[0] || null;
+  } catch (e) {
+    console.error('Failed to load emergency unlock', e);
+  }
+}
+
+// PIN 和口令只发送给后端保存哈希，前端不保存
+async function saveEmergencyUnlock(method, secret = null) {
+  try {
+    await invoke('set_emergency_unlock', { method, secret });
+    emergencyUnlock.method = method;
+    emergencyUnlock.pending = null;
+    emergencyUnlock.error = null;
+  } catch (e) {
+    emergencyUnlock.error = String(e);
+  }
+  renderFullUI();
+}
+
+function syncCallDetection() {
+  invoke('set_call_detection', {
+    enabled: settings.callDetection !== false,
+    maxDeferSeconds: (settings.callMaxDefer ?? 60) * 60
+  }).catch(console.error);
+}
+
+// 转换为后端的规则格式，忽略没有填写应用的规则
+function syncAppRules() {
+  const rules = (settings.appRules || [])
+    .map(rule => ({
+      apps: rule.apps.split(',').map(app => app.trim()).filter(Boolean),
+      task_ids: rule.taskId ? [rule.taskId] : [],
+      action: rule.action === 'trigger_after'
+        ? { type: 'trigger_after', after_seconds: (rule.minutes || 50) * 60 }
+        : { type: rule.action }
+    }))
+    .filter(rule => rule.apps.length > 0);
+  invoke('set_app_rules', { rules }).catch(console.error);
+}
+
 async function saveSettings() {
   await invoke('save_settings', { settings: JSON.stringify(settings) });
 }
@@ -391,19 +613,12 @@ async function triggerNotification(task) {
     invoke('play_notification_sound').catch(() => {});
   }
   
-  // 计算合并的任务
-  let mergedTasks = [task];
-  if (settings.enableMerge) {
-    settings.tasks.forEach(t => {
-      if (t.id !== task.id && t.enabled) {
-        const remaining = countdowns[t.id];
-        // 如果剩余时间小于阈值，且没有正在推迟（或者推迟了但也快到了，这里简单起见只看remaining）
-        // 注意：countdowns可能会有延迟，但通常是准的
-        if (remaining !== undefined && remaining <= settings.mergeThreshold) {
-          mergedTasks.push(t);
-        }
-      }
-    });
+  // 合并的任务（后端在触发时按合并窗口计算）
+  const mergedTasks = (task.mergedTaskIds || [task.id])
+    .map(id => settings.tasks.find(t => t.id === id))
+    .filter(Boolean);
+  if (mergedTasks.length === 0) {
+    mergedTasks.push(task);
   }
 
   // 构建显示标题和描述
@@ -414,7 +629,8 @@ async function triggerNotification(task) {
   
   invoke('show_notification', { title: displayTitle, body: getTaskDisplayDesc(task) }).catch(console.error);
 
-  if (settings.lockScreenEnabled) {
+  // 前台全屏时后端可能要求只发通知，不进入锁屏
+  if (settings.lockScreenEnabled && !task.notifyOnly) {
     await startLockScreen(task, mergedTasks);
   } else {
     activePopup = { ...task, mergedTaskIds: mergedTasks.map(t => t.id) };
@@ -423,36 +639,36 @@ async function triggerNotification(task) {
 }
 
 async function startLockScreen(task, mergedTasks = []) {
-  // 通知后端锁屏模式激活
-  invoke('timer_set_lock_screen_active', { active: true }).catch(console.error);
-
   // 使用任务级别的锁屏时长，如果没有则使用全局设置
-  const lockDuration = task.lockDuration || settings.lockDuration;
+  const lockDuration = parseInt(task.lockDuration || settings.lockDuration);
   const mergedIds = mergedTasks.length > 0 ? mergedTasks.map(t => t.id) : [task.id];
 
   lockScreenState = {
     active: true,
     remaining: lockDuration,
+    duration: lockDuration,
     task: { ...task },
     mergedTaskIds: mergedIds,
+    title: getMergedDisplayTitle(mergedIds),
+    desc: getMergedDisplayDesc(mergedIds),
     unlockProgress: 0,
     unlockTimer: null,
     waitingConfirm: false,
+    strictMode: !!settings.strictMode,
   };
 
+  // 后端开始锁屏会话，倒计时、严格模式和结束流程都由后端负责
   try {
     await invoke('show_main_window');
     await invoke('enter_lock_mode', {
       task: {
         title: getMergedDisplayTitle(mergedIds),
         desc: getMergedDisplayDesc(mergedIds),
-        duration: parseInt(lockDuration),
+        duration: lockDuration,
         icon: task.icon,
-        strict_mode: !!settings.strictMode,
-        allow_strict_snooze: !!settings.allowStrictSnooze,
-        max_snooze_count: parseInt(settings.maxSnoozeCount),
-        snooze_minutes: parseInt(task.snoozeMinutes || 5),
-        current_snooze_count: parseInt(snoozedStatus[task.id]?.count || 0)
+        task_id: task.id,
+        task_ids: mergedIds,
+        auto_unlock: !!settings.autoUnlock
       }
     });
   } catch (e) {
@@ -460,54 +676,77 @@ async function startLockScreen(task, mergedTasks = []) {
   }
 
   renderFullUI();
+}
 
-  const lockInterval = setInterval(() => {
-    if (!lockScreenState.active) {
-      clearInterval(lockInterval);
-      return;
-    }
+// 应用后端广播的锁屏状态；本窗口尚未显示锁屏时（如网页刷新后）据此恢复
+// 主任务变化时同样重建界面
+function applyLockTick(tick) {
+  const restored = !lockScreenState.active || lockScreenState.task?.id !== tick.task_id;
+  if (restored) {
+    const task = settings.tasks.find(t => t.id === tick.task_id)
+      || { id: tick.task_id, icon: tick.icon, snoozeMinutes: tick.snooze_options[0] };
+    lockScreenState = {
+      ...lockScreenState,
+      active: true,
+      task: { ...task },
+      mergedTaskIds: tick.task_ids,
+    };
+  }
+  // 标题和描述使用会话中的文本，从属屏幕没有加载任务设置
+  lockScreenState.title = tick.title;
+  lockScreenState.desc = tick.desc;
+  const previousSnooze = snoozedStatus[tick.task_id];
+  const changed = restored
+    || lockScreenState.waitingConfirm !== tick.waiting_confirm
+    || previousSnooze?.blocked !== tick.snooze_blocked
+    || lockScreenState.inputGrabError !== tick.input_grab_error
+    || lockScreenState.emergencyAttemptsLeft !== tick.emergency_attempts_left;
+
+  lockScreenState.remaining = tick.remaining;
+  lockScreenState.duration = tick.duration;
+  lockScreenState.waitingConfirm = tick.waiting_confirm;
+  lockScreenState.strictMode = tick.strict_mode;
+  lockScreenState.inputGrabError = tick.input_grab_error; // 严格模式下未能锁定键盘和鼠标
+  lockScreenState.emergencyMethod = tick.emergency_unlock;
+  lockScreenState.emergencyAttemptsLeft = tick.emergency_attempts_left;
+  snoozedStatus[tick.task_id] = {
+    ...(previousSnooze || { active: false, remaining: 0 }),
+    options: tick.snooze_options,
+    blocked: tick.snooze_blocked
+  };
 
-    lockScreenState.remaining--;
+  if (changed) {
+    renderFullUI();
+  } else {
     updateLockScreenTimer();
-
-    if (lockScreenState.remaining <= 0) {
-      clearInterval(lockInterval);
-      if (settings.autoUnlock) {
-        endLockScreen();
-      } else {
-        showLockConfirm();
-      }
-    }
-  }, 1000);
-}
-
-function showLockConfirm() {
-  lockScreenState.waitingConfirm = true;
-  renderFullUI();
+  }
 }
 
 async function snoozeTask(minutes) {
-  const idsToSnooze = [];
-  
-  if (lockScreenState.active && lockScreenState.task) {
-    if (lockScreenState.mergedTaskIds && lockScreenState.mergedTaskIds.length > 0) {
-      idsToSnooze.push(...lockScreenState.mergedTaskIds);
-    } else {
-      idsToSnooze.push(lockScreenState.task.id);
-    }
-  } else if (activePopup) {
-    if (activePopup.mergedTaskIds && activePopup.mergedTaskIds.length > 0) {
-      idsToSnooze.push(...activePopup.mergedTaskIds);
-    } else {
-      idsToSnooze.push(activePopup.id);
+  const taskId = lockScreenState.active && lockScreenState.task
+    ? lockScreenState.task.id
+    : activePopup?.id;
+  if (!taskId) return;
+
+  // 锁屏中由后端会话推迟并结束锁屏，结果通过 lock-ended 事件返回
+  if (lockScreenState.active) {
+    try {
+      await invoke('lock_snooze', { minutes: parseInt(minutes) });
+    } catch (e) {
+      console.error('Snooze refused:', e);
+      renderFullUI();
     }
+    return;
   }
 
-  // 去重
-  const uniqueIds = [...new Set(idsToSnooze)];
-
-  for (const id of uniqueIds) {
-    await invoke('timer_snooze_task', { taskId: id, minutes: parseInt(minutes) }).catch(console.error);
+  // 后端按任务规则检查并一起推迟合并的任务，被拒绝时保持当前提醒
+  let uniqueIds;
+  try {
+    uniqueIds = await invoke('timer_snooze_task', { taskId, minutes: parseInt(minutes) });
+  } catch (e) {
+    console.error('Snooze refused:', e);
+    renderFullUI();
+    return;
   }
   
   // 从队列中移除这些已推迟的任务，防止它们作为新弹窗出现
@@ -515,45 +754,66 @@ async function snoozeTask(minutes) {
     taskQueue = taskQueue.filter(t => !uniqueIds.includes(t.id));
   }
 
-  if (lockScreenState.active) {
-    endLockScreen(true);
-  } else if (activePopup) {
+  if (activePopup) {
     activePopup = null;
     renderFullUI();
   }
 }
 
-async function endLockScreen(snoozed = false) {
-  lockScreenState.active = false;
-  lockScreenState.waitingConfirm = false;
-
-  // 通知后端锁屏模式结束
-  invoke('timer_set_lock_screen_active', { active: false }).catch(console.error);
-
-  if (!snoozed) {
-    // 重置所有合并的任务
-    const idsToReset = lockScreenState.mergedTaskIds || (lockScreenState.task ? [lockScreenState.task.id] : []);
-    
-    // 从队列中移除已合并的任务，防止解锁后再次弹窗
-    taskQueue = taskQueue.filter(t => !idsToReset.includes(t.id));
+// 严格模式下的紧急解锁入口，输入由后端校验
+function renderEmergencyUnlock() {
+  if (lockScreenState.emergencyAttemptsLeft === 0) {
+    return `<div style="color:rgba(255,255,255,0.5); font-size:0.8rem; margin-top:15px;">${t('lockScreen.emergencyTooMany')}</div>`;
+  }
+  if (!lockScreenState.emergencyOpen) {
+    return `<button class="btn btn-secondary" id="emergencyOpenBtn" style="margin-top:15px;">${t('lockScreen.emergencyOpen')}</button>`;
+  }
+  const isChallenge = lockScreenState.emergencyMethod === 'challenge';
+  const prompt = isChallenge
+    ? `<div class="lock-message" style="margin-bottom:10px;">${t('lockScreen.emergencyChallenge')}<br><b>${lockScreenState.emergencyChallenge || ''}</b></div>`
+    : '';
+  return `
+    <div class="emergency-unlock">
+      ${prompt}
+      <input type="${isChallenge ? 'text' : 'password'}" class="idle-threshold-input quiet-hours-input" id="emergencyAnswerInput" autocomplete="off" spellcheck="false"
+        placeholder="${t('lockScreen.emergencyPlaceholder_' + lockScreenState.emergencyMethod)}">
+      <button class="btn btn-secondary" id="emergencySubmitBtn">${t('lockScreen.emergencySubmit')}</button>
+      ${lockScreenState.emergencyRejected ? `<div style="color:rgba(255,255,255,0.6); font-size:0.8rem; margin-top:8px;">${t('lockScreen.emergencyWrong', { left: lockScreenState.emergencyAttemptsLeft })}</div>` : ''}
+    </div>
+  `;
+}
 
-    idsToReset.forEach(id => {
-      if (id === 'sit') stats.sitBreaks++;
-      if (id === 'water') stats.waterCups++;
-      resetTask(id);
-    });
-    
-    saveStats();
+async function openEmergencyUnlock() {
+  lockScreenState.emergencyOpen = true;
+  if (lockScreenState.emergencyMethod === 'challenge') {
+    lockScreenState.emergencyChallenge = await invoke('lock_emergency_challenge').catch(console.error);
   }
+  renderFullUI();
+}
 
+async function submitEmergencyUnlock() {
+  const input = document.getElementById('emergencyAnswerInput');
+  if (!input) return;
   try {
-    await invoke('exit_lock_mode');
-    await invoke('hide_main_window');
+    // 成功后后端结束锁屏并发送 lock-ended
+    await invoke('lock_emergency_unlock', { answer: input.value });
   } catch (e) {
-    console.error('Failed to exit lock mode', e);
+    console.error('Emergency unlock refused:', e);
+    lockScreenState.emergencyRejected = true;
+    // 答错后后端会换一句随机句子
+    if (lockScreenState.emergencyMethod === 'challenge') {
+      lockScreenState.emergencyChallenge = await invoke('lock_emergency_challenge').catch(() => null);
+    }
+    renderFullUI();
   }
+}
 
-  processNextTask();
+// 结束锁屏由后端会话判断：严格模式下拒绝提前解锁
+function requestUnlock(command) {
+  invoke(command).catch(e => {
+    console.error('Unlock refused:', e);
+    renderFullUI();
+  });
 }
 
 function updateLockScreenTimer() {
@@ -575,8 +835,7 @@ function updateLockScreenTimer() {
   }
 
   if (progressEl) {
-    // 使用任务级别的锁屏时长，如果没有则使用全局设置
-    const total = lockScreenState.task?.lockDuration || settings.lockDuration;
+    const total = lockScreenState.duration || settings.lockDuration;
     const offset = 565 * (1 - lockScreenState.remaining / total);
     progressEl.style.strokeDashoffset = offset;
   }
@@ -600,7 +859,7 @@ function startUnlockPress() {
     
     if (lockScreenState.unlockProgress >= 100) {
       cancelUnlockPress();
-      endLockScreen();
+      requestUnlock('lock_unlock');
     }
   }, 100);
 }
@@ -644,8 +903,8 @@ function dismissNotification() {
   idsToReset.forEach(id => {
     if (id === 'sit') stats.sitBreaks++;
     if (id === 'water') stats.waterCups++;
-    resetTask(id);
   });
+  completeBreak(idsToReset);
   
   activePopup = null;
   saveStats();
@@ -656,7 +915,8 @@ function addTask() {
   const id = 'task_' + Date.now();
   settings.tasks.push({
     id: id, title: t('tasks.newTask.title'), desc: t('tasks.newTask.desc'),
-    interval: 30, enabled: true, icon: 'bell', lockDuration: 60, autoResetOnIdle: true, preNotificationSeconds: 5, snoozeMinutes: 5
+    interval: 30, enabled: true, icon: 'bell', lockDuration: 60, autoResetOnIdle: true, preNotificationSeconds: 5, snoozeMinutes: 5,
+    inhibitPolicy: 'passive'
   });
   countdowns[id] = 30 * 60;
   saveSettings();
@@ -664,6 +924,21 @@ function addTask() {
   renderFullUI();
 }
 
+// 番茄钟：工作 25 分钟，短休息 5 分钟，每 4 个番茄钟长休息 15 分钟
+function addPomodoroTask() {
+  const id = 'pomodoro_' + Date.now();
+  settings.tasks.push({
+    id: id, title: t('tasks.pomodoro.title'), desc: t('tasks.pomodoro.desc'),
+    interval: 25, enabled: true, icon: 'bell', lockDuration: 300, autoResetOnIdle: false, preNotificationSeconds: 5, snoozeMinutes: 5,
+    inhibitPolicy: 'active',
+    schedule: { type: 'pomodoro', short_break: 5, long_break: 15, long_break_every: 4 }
+  });
+  countdowns[id] = 25 * 60;
+  saveSettings();
+  syncTasksToBackend();
+  renderFullUI();
+}
+
 function removeTask(id) {
   settings.tasks = settings.tasks.filter(t => t.id !== id);
   delete countdowns[id];
@@ -675,12 +950,7 @@ function removeTask(id) {
 function resetTask(id) {
   const task = settings.tasks.find(t => t.id === id);
   if (task) {
-    countdowns[id] = task.interval * 60;
-    // 重置时清除推迟状态
-    if (snoozedStatus[id]) {
-      snoozedStatus[id].active = false;
-      snoozedStatus[id].remaining = 0;
-    }
+    clearTaskCountdown(task);
     // 通知后端重置该任务
     invoke('timer_reset_task', { taskId: id }).catch(console.error);
     updateTrayTooltip(true);
@@ -688,6 +958,28 @@ function resetTask(id) {
   }
 }
 
+function clearTaskCountdown(task) {
+  countdowns[task.id] = task.interval * 60;
+  delete coveredBy[task.id];
+  // 重置时清除推迟状态
+  if (snoozedStatus[task.id]) {
+    snoozedStatus[task.id].active = false;
+    snoozedStatus[task.id].remaining = 0;
+  }
+}
+
+// 完成一次休息：后端重置提醒中的任务及其抵扣的任务，被抵扣的任务不再排队提醒
+async function completeBreak(ids) {
+  const resetIds = await invoke('timer_complete_break', { taskIds: ids }).catch(e => {
+    console.error(e);
+    return ids;
+  });
+  taskQueue = taskQueue.filter(t => !resetIds.includes(t.id));
+  settings.tasks.filter(t => resetIds.includes(t.id)).forEach(clearTaskCountdown);
+  updateTrayTooltip(true);
+  updateLiveValues();
+}
+
 function updateTask(id, updates) {
   const task = settings.tasks.find(t => t.id === id);
   if (task) {
@@ -702,13 +994,19 @@ function updateTask(id, updates) {
 }
 
 function togglePause() {
-  isPaused = !isPaused;
   // 通知后端暂停/恢复
-  if (isPaused) {
+  if (!isPaused) {
     invoke('timer_pause').catch(console.error);
   } else {
     invoke('timer_resume').catch(console.error);
   }
+  setPaused(!isPaused);
+}
+
+// 更新暂停状态的显示，后端状态由调用方负责
+function setPaused(paused) {
+  isPaused = paused;
+  pauseRemaining = null;
   invoke('update_pause_menu', { paused: isPaused }).catch(() => {});
   updateTrayTooltip(true);
   renderFullUI();
@@ -725,6 +1023,7 @@ function resetAll() {
     }
   });
   isPaused = false;
+  pauseRemaining = null;
   invoke('timer_resume').catch(console.error);
   updateTrayTooltip(true);
   renderFullUI();
@@ -736,6 +1035,28 @@ function formatTime(seconds) {
   return `${String(mins).padStart(2, '0')}:${String(secs).padStart(2, '0')}`;
 }
 
+// 格式化剩余暂停时间，超过一小时显示为 h:mm:ss
+function formatPauseRemaining(seconds) {
+  if (seconds < 3600) {
+    return formatTime(seconds);
+  }
+  const hours = Math.floor(seconds / 3600);
+  return `${hours}:${formatTime(seconds % 3600)}`;
+}
+
+// 暂停状态文本，定时暂停时附带剩余时间
+function getPausedText() {
+  return pauseRemaining != null
+    ? t('status.pausedFor', { time: formatPauseRemaining(pauseRemaining) })
+    : t('status.paused');
+}
+
+// 格式化时间戳为当地时刻，如 10:30
+function formatClockTime(timestamp) {
+  const date = new Date(timestamp);
+  return `${String(date.getHours()).padStart(2, '0')}:${String(date.getMinutes()).padStart(2, '0')}`;
+}
+
 function formatLockTime(seconds) {
   if (seconds >= 60) {
     const mins = Math.floor(seconds / 60);
@@ -812,6 +1133,16 @@ function cacheDomRefs() {
   });
 }
 
+// 番茄钟的阶段、本组进度和今天的完成数
+function getPomodoroText(pomodoro) {
+  return t('pomodoro.progress', {
+    phase: t('pomodoro.phase_' + pomodoro.phase),
+    cycle: pomodoro.cycle,
+    cycles: pomodoro.cycles,
+    completed: pomodoro.completed
+  });
+}
+
 function updateTrayTooltip(force = false) {
   const now = Date.now();
   if (!force && now - lastTrayTooltipUpdateAt < TRAY_TOOLTIP_MIN_INTERVAL_MS) {
@@ -820,11 +1151,15 @@ function updateTrayTooltip(force = false) {
 
   const lines = [t('app.trayTooltip')];
   if (isPaused) {
-    lines.push('(' + t('status.paused') + ')');
+    lines.push('(' + getPausedText() + ')');
+  } else if (quietUntil) {
+    lines.push('(' + t('status.quietUntil', { time: formatClockTime(quietUntil) }) + ')');
   } else {
     settings.tasks.forEach(t_task => {
       if (t_task.enabled) {
-        lines.push(`${getTaskDisplayTitle(t_task)}：${formatTime(countdowns[t_task.id] ?? 0)}`);
+        const pomodoro = pomodoroStatus[t_task.id];
+        const suffix = pomodoro ? ` · ${getPomodoroText(pomodoro)}` : '';
+        lines.push(`${getTaskDisplayTitle(t_task)}：${formatTime(countdowns[t_task.id] ?? 0)}${suffix}`);
       }
     });
   }
@@ -839,6 +1174,14 @@ function updateTrayTooltip(force = false) {
   invoke('update_tray_tooltip', { tooltip: text }).catch(() => {});
 }
 
+// 空闲检测后端说明
+function getIdleBackendText() {
+  if (idleBackend === 'none') {
+    return t('settings.idleBackendUnavailable');
+  }
+  return t('settings.idleBackend', { backend: idleBackend });
+}
+
 function updateLiveValues() {
   if (isUiSuspended) {
     updateTrayTooltip();
@@ -857,7 +1200,7 @@ function updateLiveValues() {
   let nextTask = null;
   let minTime = Infinity;
   settings.tasks.forEach(t => {
-    if (t.enabled && countdowns[t.id] < minTime) {
+    if (t.enabled && !outsideHours[t.id] && !passiveTasks[t.id] && countdowns[t.id] < minTime) {
       minTime = countdowns[t.id];
       nextTask = t;
     }
@@ -873,7 +1216,9 @@ function updateLiveValues() {
   if (domCache.timerLabel) {
     let statusText = nextTask ? getTaskDisplayTitle(nextTask) : t('status.noActiveTask');
     if (isPaused) {
-      statusText += ' (' + t('status.paused') + ')';
+      statusText += ' (' + getPausedText() + ')';
+    } else if (quietUntil) {
+      statusText += ' (' + t('status.quietUntil', { time: formatClockTime(quietUntil) }) + ')';
     } else if (isIdle) {
       statusText += ' (' + t('status.idle') + ')';
     }
@@ -881,7 +1226,7 @@ function updateLiveValues() {
   }
 
   if (domCache.mainRingProgress && nextTask) {
-    const total = nextTask.interval * 60;
+    const total = countdownTotals[nextTask.id] ?? nextTask.interval * 60;
     if (total > 0) {
       const offset = 502 * (1 - (countdowns[nextTask.id] ?? 0) / total);
       domCache.mainRingProgress.style.strokeDashoffset = offset;
@@ -893,7 +1238,7 @@ function updateLiveValues() {
     if (!cardRefs) return;
 
     let current = countdowns[task.id] || 0;
-    let total = task.interval * 60;
+    let total = countdownTotals[task.id] ?? task.interval * 60;
     const snoozeState = snoozedStatus[task.id];
     const isSnoozed = snoozeState && snoozeState.active;
 
@@ -908,13 +1253,53 @@ function updateLiveValues() {
     }
 
     if (cardRefs.timeDisplay) {
-      if (isSnoozed) {
+      const offHours = outsideHours[task.id];
+      if (offHours) {
+        cardRefs.card.classList.remove('snoozed');
+        cardRefs.timeDisplay.innerText = offHours.resumesAt
+          ? t('status.outsideHoursUntil', { time: formatClockTime(offHours.resumesAt) })
+          : t('status.outsideHours');
+        cardRefs.timeDisplay.style.color = 'var(--text-muted)';
+      } else if (deferredTasks[task.id]) {
+        cardRefs.card.classList.remove('snoozed');
+        const { reason, app } = deferredTasks[task.id];
+        cardRefs.timeDisplay.innerText = `(${t('status.deferred_' + reason, { app })})`;
+        cardRefs.timeDisplay.style.color = 'var(--warning)';
+      } else if (coveredBy[task.id]) {
+        cardRefs.card.classList.remove('snoozed');
+        const coveringTask = settings.tasks.find(t => t.id === coveredBy[task.id]);
+        cardRefs.timeDisplay.innerText = `(${t('status.coveredBy', { task: coveringTask ? getTaskDisplayTitle(coveringTask) : coveredBy[task.id] })})`;
+        cardRefs.timeDisplay.style.color = 'var(--text-muted)';
+      } else if (unansweredSince[task.id]) {
+        cardRefs.card.classList.remove('snoozed');
+        const minutes = Math.floor((Date.now() - unansweredSince[task.id]) / 60000);
+        cardRefs.timeDisplay.innerText = `(${t('status.unanswered', { minutes })})`;
+        cardRefs.timeDisplay.style.color = 'var(--warning)';
+      } else if (passiveTasks[task.id]) {
+        cardRefs.card.classList.remove('snoozed');
+        cardRefs.timeDisplay.innerText = `(${formatTime(current)} · ${t('status.mediaPaused')})`;
+        cardRefs.timeDisplay.style.color = 'var(--text-muted)';
+      } else if (inactiveTasks[task.id]) {
+        cardRefs.card.classList.remove('snoozed');
+        cardRefs.timeDisplay.innerText = `(${formatTime(current)} · ${t('status.waitingInput')})`;
+        cardRefs.timeDisplay.style.color = 'var(--text-muted)';
+      } else if (appPausedTasks[task.id]) {
+        cardRefs.card.classList.remove('snoozed');
+        cardRefs.timeDisplay.innerText = `(${formatTime(current)} · ${t('status.appPaused')})`;
+        cardRefs.timeDisplay.style.color = 'var(--text-muted)';
+      } else if (isSnoozed) {
         cardRefs.card.classList.add('snoozed');
         cardRefs.timeDisplay.innerText = t('status.snoozed') + ' ' + formatTime(current);
         cardRefs.timeDisplay.style.color = 'var(--warning)';
       } else {
         cardRefs.card.classList.remove('snoozed');
-        cardRefs.timeDisplay.innerText = `(${formatTime(current)})`;
+        const dueAt = nextDueAt[task.id];
+        const pomodoro = pomodoroStatus[task.id];
+        cardRefs.timeDisplay.innerText = dueAt
+          ? `(${formatTime(current)} · ${t('taskCard.nextAt', { time: formatClockTime(dueAt) })})`
+          : pomodoro
+            ? `(${formatTime(current)} · ${getPomodoroText(pomodoro)})`
+            : `(${formatTime(current)})`;
         cardRefs.timeDisplay.style.color = '';
       }
     }
@@ -997,11 +1382,52 @@ function renderFullUI() {
               <input type="number" class="lock-input snooze-input" value="${task.snoozeMinutes || 5}" data-id="${task.id}" min="1" max="60">
               <span>${t('time.minutes')}</span>
             </div>
+            ${task.schedule?.type === 'pomodoro' ? `
+            <div class="footer-option">
+              <span>${t('taskCard.pomodoroBreaks')}</span>
+              <input type="number" class="lock-input pomodoro-input" value="${task.schedule.short_break}" data-id="${task.id}" data-field="short_break" title="${t('pomodoro.phase_short_break')}" min="1" max="60">
+              <span>/</span>
+              <input type="number" class="lock-input pomodoro-input" value="${task.schedule.long_break}" data-id="${task.id}" data-field="long_break" title="${t('pomodoro.phase_long_break')}" min="1" max="120">
+              <span>${t('time.minutes')}</span>
+            </div>
+            <div class="footer-option">
+              <span>${t('taskCard.pomodoroLongEvery')}</span>
+              <input type="number" class="lock-input pomodoro-input" value="${task.schedule.long_break_every}" data-id="${task.id}" data-field="long_break_every" min="1" max="12">
+            </div>` : `
             <div class="footer-option">
               <span>${t('taskCard.lockDuration')}</span>
-              <input type="number" class="lock-input" value="${task.lockDuration || settings.lockDuration}" data-id="${task.id}" min="5" max="3600">
+              <input type="number" class="lock-input lock-duration-input" value="${task.lockDuration || settings.lockDuration}" data-id="${task.id}" min="5" max="3600">
+              <span>${t('time.seconds')}</span>
+            </div>`}
+            <div class="footer-option">
+              <span>${t('taskCard.breakAfterIdle')}</span>
+              <input type="number" class="lock-input break-idle-input" value="${task.idleThreshold ?? ''}" placeholder="${settings.idleThreshold}" data-id="${task.id}" min="5" max="3600">
               <span>${t('time.seconds')}</span>
             </div>
+            <div class="footer-option">
+              <span>${t('taskCard.inhibitPolicy')}</span>
+              <select class="task-select inhibit-policy-select" data-id="${task.id}">
+                ${['passive', 'idle', 'active'].map(p => `<option value="${p}" ${(task.inhibitPolicy || 'passive') === p ? 'selected' : ''}>${t('taskCard.inhibit_' + p)}</option>`).join('')}
+              </select>
+            </div>
+            <div class="footer-option">
+              <span>${t('taskCard.escalation')}</span>
+              <select class="task-select escalation-select" data-id="${task.id}">
+                ${Object.keys(ESCALATION_PRESETS).map(p => `<option value="${p}" ${(task.escalation || 'off') === p ? 'selected' : ''}>${t('taskCard.escalation_' + p)}</option>`).join('')}
+              </select>
+            </div>
+            ${settings.tasks.length > 1 ? `
+            <div class="footer-option">
+              <span>${t('taskCard.satisfies')}</span>
+              ${settings.tasks.filter(other => other.id !== task.id).map(other => `
+              <label class="satisfies-option"><input type="checkbox" class="satisfies-checkbox" data-id="${task.id}" data-target="${other.id}" ${(task.satisfies || []).includes(other.id) ? 'checked' : ''}>${getTaskDisplayTitle(other)}</label>`).join('')}
+            </div>` : ''}
+            <div class="footer-option">
+              <span>${t('taskCard.countMode')}</span>
+              <select class="task-select count-mode-select" data-id="${task.id}">
+                ${['wall_clock', 'active_time'].map(m => `<option value="${m}" ${(task.countMode || 'wall_clock') === m ? 'selected' : ''}>${t('taskCard.countMode_' + m)}</option>`).join('')}
+              </select>
+            </div>
           </div>
         </div>
         `;
@@ -1009,6 +1435,7 @@ function renderFullUI() {
     </div>
 
     <button class="add-task-btn" id="addTaskBtn">${ICONS.plus} ${t('buttons.addTask')}</button>
+    <button class="add-task-btn" id="addPomodoroBtn">${ICONS.plus} ${t('buttons.addPomodoro')}</button>
 
     <div class="quick-actions">
       <button class="btn btn-primary" id="pauseBtn">${isPaused ? ICONS.play : ICONS.pause} ${isPaused ? t('buttons.resume') : t('buttons.pause')}</button>
@@ -1031,6 +1458,19 @@ function renderFullUI() {
         </div>
         <div class="toggle ${settings.strictMode ? 'active' : ''}" id="strictModeToggle"></div>
       </div>
+      <div class="setting-row">
+        <div class="setting-info">
+          <label>${t('settings.emergencyUnlock')}</label>
+          <span class="setting-desc">${emergencyUnlock.error || (emergencyUnlock.last ? t('settings.emergencyUnlockLast', { time: new Date(emergencyUnlock.last.time).toLocaleString() }) : t('settings.emergencyUnlockDesc'))}</span>
+        </div>
+        <select id="emergencyMethodSelect">
+          ${['off', 'pin', 'passphrase', 'challenge'].map(method => `<option value="${method}" ${(emergencyUnlock.pending || emergencyUnlock.method || 'off') === method ? 'selected' : ''}>${t('settings.emergencyUnlock_' + method)}</option>`).join('')}
+        </select>
+      </div>
+      <div class="setting-row" style="display: ${emergencyUnlock.pending ? 'flex' : 'none'};">
+        <input type="password" class="idle-threshold-input quiet-hours-input" id="emergencySecretInput" autocomplete="new-password" placeholder="${t(emergencyUnlock.pending === 'pin' ? 'settings.emergencyPinPlaceholder' : 'settings.emergencyPassphrasePlaceholder')}">
+        <button class="btn btn-secondary" id="emergencySaveBtn">${t('buttons.save')}</button>
+      </div>
 
       <div class="setting-row" id="advancedToggle" style="cursor:pointer; opacity:0.7;">
         <div style="display:flex; align-items:center; gap:8px;">
@@ -1084,10 +1524,78 @@ function renderFullUI() {
           </div>
         </div>
 
+        <div class="setting-row">
+          <div class="setting-info">
+            <label>${t('settings.quietHours')}</label>
+            <span class="setting-desc">${t('settings.quietHoursDesc')}</span>
+          </div>
+          <div class="toggle ${settings.quietHoursEnabled ? 'active' : ''}" id="quietHoursToggle"></div>
+        </div>
+
+        <div class="setting-row" id="quietHoursRow" style="display: ${settings.quietHoursEnabled ? 'flex' : 'none'};">
+          <input type="text" class="idle-threshold-input quiet-hours-input" id="quietHoursInput" value="${settings.quietHours}" placeholder="19:00-09:00, 12:00-13:00">
+          <select id="quietPolicySelect" title="${t('settings.quietPolicy')}">
+            ${['drop', 'defer', 'collapse'].map(policy => `<option value="${policy}" ${settings.quietPolicy === policy ? 'selected' : ''}>${t('settings.quietPolicy_' + policy)}</option>`).join('')}
+          </select>
+        </div>
+
+        <div class="setting-row">
+          <div class="setting-info">
+            <label>${t('settings.fullscreenPolicy')}</label>
+            <span class="setting-desc">${t('settings.fullscreenPolicyDesc')}</span>
+          </div>
+          <div class="idle-threshold-input-group">
+            <select id="fullscreenPolicySelect">
+              ${['ignore', 'defer', 'notify_only'].map(policy => `<option value="${policy}" ${settings.fullscreenPolicy === policy ? 'selected' : ''}>${t('settings.fullscreenPolicy_' + policy)}</option>`).join('')}
+            </select>
+            <input type="number" class="idle-threshold-input" id="fullscreenMaxDeferInput" value="${settings.fullscreenMaxDefer ?? 30}" min="0" max="240" style="display: ${settings.fullscreenPolicy === 'defer' ? 'block' : 'none'};">
+            <span class="input-unit" style="display: ${settings.fullscreenPolicy === 'defer' ? 'inline' : 'none'};">${t('time.minutes')}</span>
+          </div>
+        </div>
+
+        <div class="setting-row">
+          <div class="setting-info">
+            <label>${t('settings.callDetection')}</label>
+            <span class="setting-desc">${t('settings.callDetectionDesc')}</span>
+          </div>
+          <div class="idle-threshold-input-group">
+            <input type="number" class="idle-threshold-input" id="callMaxDeferInput" value="${settings.callMaxDefer ?? 60}" min="0" max="240" style="display: ${settings.callDetection !== false ? 'block' : 'none'};">
+            <span class="input-unit" style="display: ${settings.callDetection !== false ? 'inline' : 'none'};">${t('time.minutes')}</span>
+            <div class="toggle ${settings.callDetection !== false ? 'active' : ''}" id="callDetectionToggle"></div>
+          </div>
+        </div>
+
+        <div class="setting-row">
+          <div class="setting-info">
+            <label>${t('settings.appRules')}</label>
+            <span class="setting-desc">${t('settings.appRulesDesc')}</span>
+          </div>
+          <button class="check-update-btn" id="addAppRuleBtn">${t('buttons.addAppRule')}</button>
+        </div>
+
+        ${(settings.appRules || []).map((rule, i) => `
+        <div class="setting-row app-rule-row">
+          <input type="text" class="idle-threshold-input quiet-hours-input app-rule-apps" data-index="${i}" value="${rule.apps}" placeholder="zoom, obs">
+          <select class="app-rule-action" data-index="${i}">
+            ${['defer', 'notify_only', 'only_while', 'trigger_after'].map(action => `<option value="${action}" ${rule.action === action ? 'selected' : ''}>${t('settings.appRuleAction_' + action)}</option>`).join('')}
+          </select>
+          <select class="app-rule-task" data-index="${i}">
+            <option value="">${t('settings.appRuleAllTasks')}</option>
+            ${settings.tasks.map(task => `<option value="${task.id}" ${rule.taskId === task.id ? 'selected' : ''}>${getTaskDisplayTitle(task)}</option>`).join('')}
+          </select>
+          ${rule.action === 'trigger_after' ? `
+          <input type="number" class="idle-threshold-input app-rule-minutes" data-index="${i}" value="${rule.minutes || 50}" min="1" max="600">
+          <span class="input-unit">${t('time.minutes')}</span>
+          ` : ''}
+          <div class="app-rule-remove" data-index="${i}" style="cursor:pointer; padding:4px;">${ICONS.trash}</div>
+        </div>
+        `).join('')}
+
         <div class="setting-row">
           <div class="setting-info">
             <label>${t('settings.idleThreshold')}</label>
             <span class="setting-desc">${isIdle ? t('settings.idleThresholdDescIdle') : t('settings.idleThresholdDesc')}</span>
+            <span class="setting-desc" id="idleBackendDesc">${getIdleBackendText()}</span>
           </div>
           <div class="idle-threshold-input-group">
             <input type="number" class="idle-threshold-input" id="idleThresholdInput" value="${Math.floor(settings.idleThreshold / 60)}" min="1" max="60">
@@ -1095,6 +1603,17 @@ function renderFullUI() {
           </div>
         </div>
 
+        <div class="setting-row">
+          <div class="setting-info">
+            <label>${t('settings.offlineBreakThreshold')}</label>
+            <span class="setting-desc">${t('settings.offlineBreakThresholdDesc')}</span>
+          </div>
+          <div class="idle-threshold-input-group">
+            <input type="number" class="idle-threshold-input" id="offlineBreakThresholdInput" value="${Math.floor(settings.offlineBreakThreshold / 60)}" min="1" max="240">
+            <span class="input-unit">${t('time.minutes')}</span>
+          </div>
+        </div>
+
         <div class="setting-row">
           <div class="setting-info">
             <label>${t('settings.maxSnoozeCount')}</label>
@@ -1149,9 +1668,8 @@ function renderFullUI() {
         <div style="display:flex; justify-content:center; gap:10px;">
           <button class="btn btn-primary" id="dismissBtn">${t('buttons.gotIt')}</button>
           ${(() => {
-            const count = (activePopup && snoozedStatus[activePopup.id]) ? snoozedStatus[activePopup.id].count : 0;
-            const isStrictRestricted = settings.strictMode && !settings.allowStrictSnooze;
-            if (count < settings.maxSnoozeCount && !isStrictRestricted) {
+            const snoozeState = activePopup ? snoozedStatus[activePopup.id] : null;
+            if (snoozeState && !snoozeState.blocked) {
               return `<button class="btn btn-secondary" id="popupSnoozeBtn">${t('buttons.snooze', { minutes: activePopup ? (activePopup.snoozeMinutes || 5) : 5 })}</button>`;
             }
             return '';
@@ -1179,15 +1697,16 @@ function renderFullUI() {
             <div class="lock-unit">${lockScreenState.waitingConfirm ? t('buttons.confirmRest').split(' ')[0] : formatLockTime(lockScreenState.remaining).unit}</div>
           </div>
         </div>
-        <div class="lock-title">${lockScreenState.waitingConfirm ? t('lockScreen.timeUp') : (lockScreenState.task ? (lockScreenState.mergedTaskIds ? getMergedDisplayTitle(lockScreenState.mergedTaskIds) : getTaskDisplayTitle(lockScreenState.task)) : t('lockScreen.restTime'))}</div>
-        <div class="lock-message">${lockScreenState.waitingConfirm ? t('lockScreen.confirmMessage') : (lockScreenState.task ? (lockScreenState.mergedTaskIds ? getMergedDisplayDesc(lockScreenState.mergedTaskIds) : getTaskDisplayDesc(lockScreenState.task)) : t('lockScreen.restMessage'))}</div>
+        <div class="lock-title">${lockScreenState.waitingConfirm ? t('lockScreen.timeUp') : (lockScreenState.title || t('lockScreen.restTime'))}</div>
+        <div class="lock-message">${lockScreenState.waitingConfirm ? t('lockScreen.confirmMessage') : (lockScreenState.desc || t('lockScreen.restMessage'))}</div>
+        ${lockScreenState.inputGrabError && !lockScreenState.waitingConfirm ? `<div style="color:rgba(255,255,255,0.5); font-size:0.8rem; margin-bottom:15px;" title="${lockScreenState.inputGrabError}">${t('lockScreen.inputGrabFailed')}</div>` : ''}
         ${lockScreenState.waitingConfirm ? `
         <button class="confirm-btn" id="confirmBtn">
           <svg xmlns="http://www.w3.org/2000/svg" width="20" height="20" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="3" stroke-linecap="round" stroke-linejoin="round"><polyline points="20 6 9 17 4 12"></polyline></svg>
           ${t('buttons.confirmRest')}
         </button>
         ` : `
-        ${settings.strictMode ? '' : `
+        ${lockScreenState.strictMode || isLockSlave ? '' : `
         <button class="unlock-btn" id="unlockBtn">
           <div class="unlock-progress"></div>
           <div class="unlock-text">
@@ -1196,14 +1715,18 @@ function renderFullUI() {
           </div>
         </button>
         `}
+        ${lockScreenState.strictMode && lockScreenState.emergencyMethod ? renderEmergencyUnlock() : ''}
         ${(() => {
-          const count = (lockScreenState.task && snoozedStatus[lockScreenState.task.id]) ? snoozedStatus[lockScreenState.task.id].count : 0;
-          const isStrictRestricted = settings.strictMode && !settings.allowStrictSnooze;
-
-          if (count >= settings.maxSnoozeCount) {
-            return '<div style="color:rgba(255,255,255,0.5); font-size:0.8rem; margin-top:15px;">' + t('lockScreen.snoozeLimit') + '</div>';
-          } else if (isStrictRestricted) {
-            return '<div style="color:rgba(255,255,255,0.5); font-size:0.8rem; margin-top:15px;">' + t('lockScreen.strictDisabled') + '</div>';
+          // 能否推迟由后端按任务规则判断
+          const snoozeState = lockScreenState.task ? snoozedStatus[lockScreenState.task.id] : null;
+          const blockedText = {
+            limit_reached: t('lockScreen.snoozeLimit'),
+            strict_mode: t('lockScreen.strictDisabled'),
+            cooling_down: t('lockScreen.snoozeCooldown'),
+          }[snoozeState?.blocked];
+
+          if (!snoozeState || snoozeState.blocked) {
+            return blockedText ? '<div style="color:rgba(255,255,255,0.5); font-size:0.8rem; margin-top:15px;">' + blockedText + '</div>' : '';
           } else {
             return `
             <button id="lockSnoozeBtn" style="margin-top:15px; background:rgba(255,255,255,0.2); border:none; padding:8px 16px; border-radius:20px; color:white; font-size:14px; cursor:pointer;">
@@ -1279,6 +1802,8 @@ function bindEvents() {
         settings.strictMode = !settings.strictMode;
         el.classList.toggle('active', settings.strictMode);
         saveSettings();
+        // 严格模式的锁屏期间后端拒绝关闭
+        invoke('timer_set_strict_mode', { enabled: settings.strictMode }).catch(console.error);
       } else if (el.id === 'resetOnIdleToggle') {
         settings.resetOnIdle = !settings.resetOnIdle;
         el.classList.toggle('active', settings.resetOnIdle);
@@ -1288,11 +1813,25 @@ function bindEvents() {
         settings.allowStrictSnooze = !settings.allowStrictSnooze;
         el.classList.toggle('active', settings.allowStrictSnooze);
         saveSettings();
+        syncTasksToBackend();
         renderFullUI();
       } else if (el.id === 'enableMergeToggle') {
         settings.enableMerge = !settings.enableMerge;
         el.classList.toggle('active', settings.enableMerge);
         saveSettings();
+        syncMergeWindow();
+        renderFullUI();
+      } else if (el.id === 'quietHoursToggle') {
+        settings.quietHoursEnabled = !settings.quietHoursEnabled;
+        el.classList.toggle('active', settings.quietHoursEnabled);
+        saveSettings();
+        syncQuietHours();
+        renderFullUI();
+      } else if (el.id === 'callDetectionToggle') {
+        settings.callDetection = settings.callDetection === false;
+        el.classList.toggle('active', settings.callDetection);
+        saveSettings();
+        syncCallDetection();
         renderFullUI();
       }
     });
@@ -1377,7 +1916,7 @@ function bindEvents() {
   });
 
   // 任务级别的锁屏时长输入框
-  document.querySelectorAll('.lock-input:not(.pre-notify-input)').forEach(el => {
+  document.querySelectorAll('.lock-duration-input').forEach(el => {
     el.addEventListener('input', (e) => {
       const id = el.dataset.id;
       const task = settings.tasks.find(t => t.id === id);
@@ -1389,6 +1928,86 @@ function bindEvents() {
     });
   });
 
+  // 番茄钟的休息时长和长休息间隔，修改后后端重新开始计时
+  document.querySelectorAll('.pomodoro-input').forEach(el => {
+    el.addEventListener('change', (e) => {
+      const task = settings.tasks.find(t => t.id === el.dataset.id);
+      const val = parseInt(e.target.value);
+      if (task && task.schedule && val >= 1) {
+        task.schedule = { ...task.schedule, [el.dataset.field]: val };
+        saveSettings();
+        syncTasksToBackend();
+      }
+    });
+  });
+
+  // 完成本任务的休息时一并视为完成休息的任务
+  document.querySelectorAll('.satisfies-checkbox').forEach(el => {
+    el.addEventListener('change', (e) => {
+      const task = settings.tasks.find(t => t.id === el.dataset.id);
+      if (task) {
+        const others = (task.satisfies || []).filter(id => id !== el.dataset.target);
+        task.satisfies = e.target.checked ? [...others, el.dataset.target] : others;
+        saveSettings();
+        syncTasksToBackend();
+      }
+    });
+  });
+
+  // 任务级别的屏保抑制策略
+  document.querySelectorAll('.inhibit-policy-select').forEach(el => {
+    el.addEventListener('change', (e) => {
+      const task = settings.tasks.find(t => t.id === el.dataset.id);
+      if (task) {
+        task.inhibitPolicy = e.target.value;
+        saveSettings();
+        syncTasksToBackend();
+      }
+    });
+  });
+
+  // 任务级别的升级方案
+  document.querySelectorAll('.escalation-select').forEach(el => {
+    el.addEventListener('change', (e) => {
+      const task = settings.tasks.find(t => t.id === el.dataset.id);
+      if (task) {
+        task.escalation = e.target.value;
+        saveSettings();
+        syncTasksToBackend();
+      }
+    });
+  });
+
+  // 任务级别的计时方式
+  document.querySelectorAll('.count-mode-select').forEach(el => {
+    el.addEventListener('change', (e) => {
+      const task = settings.tasks.find(t => t.id === el.dataset.id);
+      if (task) {
+        task.countMode = e.target.value;
+        saveSettings();
+        syncTasksToBackend();
+      }
+    });
+  });
+
+  // 任务级别的自然休息阈值，留空使用全局空闲阈值
+  document.querySelectorAll('.break-idle-input').forEach(el => {
+    el.addEventListener('change', (e) => {
+      const task = settings.tasks.find(t => t.id === el.dataset.id);
+      if (!task) return;
+      const val = parseInt(e.target.value);
+      if (e.target.value === '') {
+        delete task.idleThreshold;
+      } else if (val >= 5) {
+        task.idleThreshold = val;
+      } else {
+        return;
+      }
+      saveSettings();
+      syncTasksToBackend();
+    });
+  });
+
   // 任务级别的预告时间输入框
   document.querySelectorAll('.pre-notify-input').forEach(el => {
     el.addEventListener('input', (e) => {
@@ -1411,11 +2030,13 @@ function bindEvents() {
       if (task && val >= 1) {
         task.snoozeMinutes = val;
         saveSettings();
+        syncTasksToBackend();
       }
     });
   });
 
   document.getElementById('addTaskBtn').onclick = addTask;
+  document.getElementById('addPomodoroBtn').onclick = addPomodoroTask;
   document.getElementById('pauseBtn').onclick = togglePause;
   document.getElementById('resetBtn').onclick = resetAll;
   document.getElementById('dismissBtn').onclick = dismissNotification;
@@ -1455,7 +2076,52 @@ function bindEvents() {
 
   const confirmBtn = document.getElementById('confirmBtn');
   if (confirmBtn) {
-    confirmBtn.addEventListener('click', endLockScreen);
+    confirmBtn.addEventListener('click', () => requestUnlock('lock_confirm'));
+  }
+
+  const emergencyOpenBtn = document.getElementById('emergencyOpenBtn');
+  if (emergencyOpenBtn) {
+    emergencyOpenBtn.addEventListener('click', openEmergencyUnlock);
+  }
+
+  const emergencyAnswerInput = document.getElementById('emergencyAnswerInput');
+  if (emergencyAnswerInput) {
+    emergencyAnswerInput.focus();
+    // 随机句子需要逐字输入
+    if (lockScreenState.emergencyMethod === 'challenge') {
+      emergencyAnswerInput.addEventListener('paste', (e) => e.preventDefault());
+    }
+    emergencyAnswerInput.addEventListener('keydown', (e) => {
+      if (e.key === 'Enter') submitEmergencyUnlock();
+    });
+  }
+
+  const emergencySubmitBtn = document.getElementById('emergencySubmitBtn');
+  if (emergencySubmitBtn) {
+    emergencySubmitBtn.addEventListener('click', submitEmergencyUnlock);
+  }
+
+  const emergencyMethodSelect = document.getElementById('emergencyMethodSelect');
+  if (emergencyMethodSelect) {
+    emergencyMethodSelect.addEventListener('change', (e) => {
+      const method = e.target.value;
+      if (method === 'pin' || method === 'passphrase') {
+        // 需要输入 PIN 或口令后才保存
+        emergencyUnlock.pending = method;
+        emergencyUnlock.error = null;
+        renderFullUI();
+      } else {
+        saveEmergencyUnlock(method === 'off' ? null : method);
+      }
+    });
+  }
+
+  const emergencySaveBtn = document.getElementById('emergencySaveBtn');
+  if (emergencySaveBtn) {
+    emergencySaveBtn.addEventListener('click', () => {
+      const secret = document.getElementById('emergencySecretInput').value;
+      saveEmergencyUnlock(emergencyUnlock.pending, secret);
+    });
   }
 
   const updateBtn = document.getElementById('updateBtn');
@@ -1488,6 +2154,18 @@ function bindEvents() {
     });
   }
 
+  const offlineBreakThresholdInput = document.getElementById('offlineBreakThresholdInput');
+  if (offlineBreakThresholdInput) {
+    offlineBreakThresholdInput.addEventListener('input', async (e) => {
+      const minutes = parseInt(e.target.value);
+      if (minutes >= 1 && minutes <= 240) {
+        settings.offlineBreakThreshold = minutes * 60;  // 转换为秒
+        saveSettings();
+        await invoke('set_offline_break_threshold', { seconds: settings.offlineBreakThreshold }).catch(console.error);
+      }
+    });
+  }
+
   const advancedToggle = document.getElementById('advancedToggle');
   if (advancedToggle) {
     advancedToggle.onclick = () => {
@@ -1504,10 +2182,105 @@ function bindEvents() {
       if (val >= 5) {
         settings.mergeThreshold = val;
         saveSettings();
+        syncMergeWindow();
+      }
+    });
+  }
+
+  const quietHoursInput = document.getElementById('quietHoursInput');
+  if (quietHoursInput) {
+    quietHoursInput.addEventListener('change', (e) => {
+      settings.quietHours = e.target.value;
+      saveSettings();
+      syncQuietHours();
+    });
+  }
+
+  const quietPolicySelect = document.getElementById('quietPolicySelect');
+  if (quietPolicySelect) {
+    quietPolicySelect.addEventListener('change', (e) => {
+      settings.quietPolicy = e.target.value;
+      saveSettings();
+      syncQuietHours();
+    });
+  }
+
+  const fullscreenPolicySelect = document.getElementById('fullscreenPolicySelect');
+  if (fullscreenPolicySelect) {
+    fullscreenPolicySelect.addEventListener('change', (e) => {
+      settings.fullscreenPolicy = e.target.value;
+      saveSettings();
+      syncFullscreenPolicy();
+      renderFullUI();
+    });
+  }
+
+  const fullscreenMaxDeferInput = document.getElementById('fullscreenMaxDeferInput');
+  if (fullscreenMaxDeferInput) {
+    fullscreenMaxDeferInput.addEventListener('input', (e) => {
+      const val = parseInt(e.target.value);
+      if (val >= 0) {
+        settings.fullscreenMaxDefer = val;
+        saveSettings();
+        syncFullscreenPolicy();
+      }
+    });
+  }
+
+  const callMaxDeferInput = document.getElementById('callMaxDeferInput');
+  if (callMaxDeferInput) {
+    callMaxDeferInput.addEventListener('input', (e) => {
+      const val = parseInt(e.target.value);
+      if (val >= 0) {
+        settings.callMaxDefer = val;
+        saveSettings();
+        syncCallDetection();
       }
     });
   }
 
+  const addAppRuleBtn = document.getElementById('addAppRuleBtn');
+  if (addAppRuleBtn) {
+    addAppRuleBtn.onclick = () => {
+      settings.appRules = [...(settings.appRules || []), { apps: '', action: 'notify_only', taskId: '', minutes: 50 }];
+      saveSettings();
+      renderFullUI();
+    };
+  }
+
+  // 应用规则的各个字段，修改后立即同步到后端
+  const updateAppRule = (el, updates, rerender = false) => {
+    const rule = settings.appRules[parseInt(el.dataset.index)];
+    if (!rule) return;
+    Object.assign(rule, updates);
+    saveSettings();
+    syncAppRules();
+    if (rerender) renderFullUI();
+  };
+  document.querySelectorAll('.app-rule-apps').forEach(el => {
+    el.addEventListener('change', (e) => updateAppRule(el, { apps: e.target.value }));
+  });
+  document.querySelectorAll('.app-rule-action').forEach(el => {
+    el.addEventListener('change', (e) => updateAppRule(el, { action: e.target.value }, true));
+  });
+  document.querySelectorAll('.app-rule-task').forEach(el => {
+    el.addEventListener('change', (e) => updateAppRule(el, { taskId: e.target.value }));
+  });
+  document.querySelectorAll('.app-rule-minutes').forEach(el => {
+    el.addEventListener('input', (e) => {
+      const val = parseInt(e.target.value);
+      if (val >= 1) updateAppRule(el, { minutes: val });
+    });
+  });
+  document.querySelectorAll('.app-rule-remove').forEach(el => {
+    el.addEventListener('click', () => {
+      settings.appRules.splice(parseInt(el.dataset.index), 1);
+      saveSettings();
+      syncAppRules();
+      renderFullUI();
+    });
+  });
+
   const maxSnoozeCountInput = document.getElementById('maxSnoozeCountInput');
   if (maxSnoozeCountInput) {
     maxSnoozeCountInput.addEventListener('input', (e) => {
@@ -1515,6 +2288,7 @@ function bindEvents() {
       if (count >= 0) {
         settings.maxSnoozeCount = count;
         saveSettings();
+        syncTasksToBackend();
       }
     });
   }